@@ -1,4 +1,4 @@
-use cube_core::{cube, seed};
+use cube_core::{cube, objective, seed};
 use serde::Deserialize;
 use snafu::{ensure, Snafu};
 
@@ -23,8 +23,17 @@ pub enum LevelError {
     #[snafu(display("expect movement string, but get '{}'", character))]
     InvalidMovement { character: char },
 
+    #[snafu(display("expect a repeat count, but get '{}'", count))]
+    InvalidCount { count: String },
+
+    #[snafu(display("expect commands of at most {} actions", limit))]
+    TooLongCommand { limit: usize },
+
     #[snafu(display("expect a valid location, but get ({}, {})", position.0, position.1))]
     InvalidLocation { position: (i32, i32) },
+
+    #[snafu(display("expect exactly two portals marked '{}'", character))]
+    UnpairedPortal { character: char },
 }
 
 #[derive(Deserialize)]
@@ -37,12 +46,26 @@ pub struct LevelSource {
 struct Info {
     title: String,
     author: String,
+    par: Option<usize>,
+    max_moves: Option<usize>,
 }
 
 #[derive(Deserialize)]
 struct Map {
     raw: String,
     commands: Option<Vec<Command>>,
+    demands: Option<Vec<Demand>>,
+    objectives: Option<Vec<Objective>>,
+    wires: Option<Vec<Wire>>,
+    wrap: Option<bool>,
+    gravity: Option<char>,
+    players: Option<Vec<char>>,
+}
+
+#[derive(Deserialize)]
+struct Wire {
+    plates: Vec<[i32; 2]>,
+    doors: Vec<[i32; 2]>,
 }
 
 #[derive(Deserialize)]
@@ -52,6 +75,21 @@ struct Command {
     binding: Vec<[i32; 2]>,
 }
 
+#[derive(Deserialize)]
+struct Demand {
+    kind: char,
+    binding: Vec<[i32; 2]>,
+}
+
+#[derive(Deserialize)]
+struct Objective {
+    cover: Option<bool>,
+    eliminate: Option<char>,
+    survive: Option<usize>,
+    protect: Option<[i32; 2]>,
+    within: Option<usize>,
+}
+
 impl LevelSource {
     pub fn into_seed(self) -> Result<seed::Seed, LevelError> {
         ensure!(
@@ -76,6 +114,19 @@ impl LevelSource {
                     'R' | 'r' => parser.make_cube(cube::Kind::Red),
                     'B' | 'b' => parser.make_cube(cube::Kind::Blue),
                     'G' | 'g' => parser.make_cube(cube::Kind::Green),
+                    'Y' | 'y' => parser.make_cube(cube::Kind::Yellow),
+                    'P' | 'p' => parser.make_cube(cube::Kind::Purple),
+                    '#' => parser.make_wall(),
+                    '~' => parser.make_floor(cube::Floor::Ice),
+                    '<' => parser.make_floor(cube::Floor::Conveyor(cube::Movement::Left)),
+                    'v' => parser.make_floor(cube::Floor::Conveyor(cube::Movement::Down)),
+                    '^' => parser.make_floor(cube::Floor::Conveyor(cube::Movement::Up)),
+                    '>' => parser.make_floor(cube::Floor::Conveyor(cube::Movement::Right)),
+                    '0'..='9' => parser.make_portal(c),
+                    '←' => parser.make_floor(cube::Floor::Gate(cube::Movement::Left)),
+                    '↓' => parser.make_floor(cube::Floor::Gate(cube::Movement::Down)),
+                    '↑' => parser.make_floor(cube::Floor::Gate(cube::Movement::Up)),
+                    '→' => parser.make_floor(cube::Floor::Gate(cube::Movement::Right)),
                     'X' | 'x' => parser.make_destination(),
                     ' ' => parser.make_empty(),
                     '-' => parser.copy_left()?,
@@ -86,44 +137,71 @@ impl LevelSource {
             }
             parser.mark_line_end();
         }
+        parser.pair_portals()?;
 
         for m in self.map.commands.unwrap_or_default() {
-            let mut n = String::new();
-            let mut p = CommandParser::new(m.looping);
-            for c in m.content.chars() {
-                match c {
-                    'I' => put(&mut p, &mut n).put(None),
-                    'L' => put(&mut p, &mut n).put(Some(cube::Movement::Left)),
-                    'D' => put(&mut p, &mut n).put(Some(cube::Movement::Down)),
-                    'U' => put(&mut p, &mut n).put(Some(cube::Movement::Up)),
-                    'R' => put(&mut p, &mut n).put(Some(cube::Movement::Right)),
-                    '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' if !p.is_empty() => {
-                        n.push(c)
-                    }
-                    _ => ensure!(false, InvalidMovement { character: c }),
-                }
-            }
-            if !n.is_empty() {
-                put(&mut p, &mut n);
-            }
-
-            let c: seed::Command = p.into();
+            let c = CommandParser::parse(&m.content, m.looping)?;
             for p in m.binding {
                 parser.bind_command(p[0], p[1], c.clone())?;
             }
         }
-        fn put<'a>(parser: &'a mut CommandParser, buffer: &mut String) -> &'a mut CommandParser {
-            if !buffer.is_empty() {
-                if let Ok(i) = buffer.parse::<i32>() {
-                    parser.add(i);
-                    buffer.clear();
-                }
+
+        for d in self.map.demands.unwrap_or_default() {
+            let kind = Self::kind(d.kind)?;
+            for p in d.binding {
+                parser.demand(p[0], p[1], kind)?;
             }
-            parser
+        }
+
+        for o in self.map.objectives.unwrap_or_default() {
+            if o.cover == Some(true) {
+                parser.add_objective(objective::Objective::Cover);
+            }
+            if let Some(c) = o.eliminate {
+                let kind = Self::kind(c)?;
+                parser.add_objective(objective::Objective::Eliminate(kind));
+            }
+            if let Some(n) = o.survive {
+                parser.add_objective(objective::Objective::Survive(n));
+            }
+            if let Some(p) = o.protect {
+                parser.protect(p[0], p[1])?;
+            }
+            if let Some(n) = o.within {
+                parser.add_objective(objective::Objective::Within(n));
+            }
+        }
+
+        for w in self.map.wires.unwrap_or_default() {
+            parser.add_wire(w.plates, w.doors)?;
+        }
+        parser.r = self.map.wrap.unwrap_or_default();
+        parser.g = match self.map.gravity {
+            None => None,
+            Some('L') => Some(cube::Movement::Left),
+            Some('D') => Some(cube::Movement::Down),
+            Some('U') => Some(cube::Movement::Up),
+            Some('R') => Some(cube::Movement::Right),
+            Some(c) => return Err(LevelError::InvalidMovement { character: c }),
+        };
+        for c in self.map.players.unwrap_or_default() {
+            parser.p.push(Self::kind(c)?);
         }
 
         Ok(parser.into())
     }
+
+    fn kind(c: char) -> Result<cube::Kind, LevelError> {
+        match c {
+            'W' => Ok(cube::Kind::White),
+            'R' => Ok(cube::Kind::Red),
+            'B' => Ok(cube::Kind::Blue),
+            'G' => Ok(cube::Kind::Green),
+            'Y' => Ok(cube::Kind::Yellow),
+            'P' => Ok(cube::Kind::Purple),
+            _ => Err(LevelError::InvalidMarker { character: c }),
+        }
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -136,6 +214,15 @@ struct LevelParser {
     w: i32,
     cs: Vec<seed::Cube>,
     ds: Vec<cube::Point>,
+    dk: Vec<(cube::Point, cube::Kind)>,
+    ws: Vec<cube::Point>,
+    fs: Vec<(cube::Point, cube::Floor)>,
+    ps: Vec<(char, cube::Point)>,
+    es: Vec<seed::Wire>,
+    r: bool,
+    g: Option<cube::Movement>,
+    p: Vec<cube::Kind>,
+    os: Vec<objective::Objective>,
 
     // cached
     x: i32,
@@ -145,12 +232,31 @@ struct LevelParser {
 impl Into<LevelParser> for Info {
     fn into(self) -> LevelParser {
         let (title, author) = (self.title, self.author);
-        LevelParser::new(seed::Info { title, author })
+        let (par, max_moves) = (self.par, self.max_moves);
+        LevelParser::new(seed::Info {
+            title,
+            author,
+            par,
+            max_moves,
+        })
     }
 }
 
 impl Into<seed::Seed> for LevelParser {
     fn into(mut self) -> seed::Seed {
+        // protected cubes are recorded by indexes before removing empty ones.
+        let mut indexes = Vec::with_capacity(self.cs.len());
+        let mut count = 0;
+        for c in self.cs.iter() {
+            indexes.push(count);
+            count += !c.body.is_empty() as usize;
+        }
+        for o in self.os.iter_mut() {
+            if let objective::Objective::Protect(i) = o {
+                *i = indexes[*i];
+            }
+        }
+
         self.cs.retain(|c| !c.body.is_empty());
         seed::Seed {
             info: self.i,
@@ -160,19 +266,35 @@ impl Into<seed::Seed> for LevelParser {
             },
             cubes: self.cs,
             destnations: self.ds,
+            demands: self.dk,
+            walls: self.ws,
+            floors: self.fs,
+            wires: self.es,
+            wrap: self.r,
+            gravity: self.g,
+            players: self.p,
+            objectives: self.os,
         }
     }
 }
 
 impl LevelParser {
     fn new(i: seed::Info) -> Self {
-        let (title, author) = (i.title, i.author);
         Self {
-            i: seed::Info { title, author },
+            i,
             h: 0,
             w: 0,
             cs: Vec::new(),
             ds: Vec::new(),
+            dk: Vec::new(),
+            ws: Vec::new(),
+            fs: Vec::new(),
+            ps: Vec::new(),
+            es: Vec::new(),
+            r: false,
+            g: None,
+            p: Vec::new(),
+            os: Vec::new(),
             x: 0,
             m: LevelMapBuilder(vec![Vec::new()]),
         }
@@ -199,6 +321,39 @@ impl LevelParser {
         self.make(None);
     }
 
+    fn make_wall(&mut self) {
+        self.ws.push(cube::Point::new(self.x, self.h));
+        self.make(None);
+    }
+
+    fn make_floor(&mut self, floor: cube::Floor) {
+        self.fs.push((cube::Point::new(self.x, self.h), floor));
+        self.make(None);
+    }
+
+    fn make_portal(&mut self, mark: char) {
+        self.ps.push((mark, cube::Point::new(self.x, self.h)));
+        self.make(None);
+    }
+
+    fn pair_portals(&mut self) -> Result<(), LevelError> {
+        self.ps.sort_by_key(|&(mark, _)| mark);
+        for group in self.ps.chunk_by(|l, r| l.0 == r.0) {
+            match group {
+                &[(_, l), (_, r)] => {
+                    self.fs.push((l, cube::Floor::Portal(r)));
+                    self.fs.push((r, cube::Floor::Portal(l)));
+                }
+                _ => {
+                    return Err(LevelError::UnpairedPortal {
+                        character: group[0].0,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn make_cube(&mut self, kind: cube::Kind) {
         let i = self.cs.len();
         let c = seed::Cube {
@@ -295,6 +450,43 @@ impl LevelParser {
         }
     }
 
+    fn add_objective(&mut self, objective: objective::Objective) {
+        self.os.push(objective);
+    }
+
+    fn protect(&mut self, x: i32, y: i32) -> Result<(), LevelError> {
+        match self.m.get(x, y) {
+            Some(i) => {
+                self.os.push(objective::Objective::Protect(i));
+                Ok(())
+            }
+            None => Err(LevelError::InvalidLocation { position: (x, y) }),
+        }
+    }
+
+    fn add_wire(&mut self, plates: Vec<[i32; 2]>, doors: Vec<[i32; 2]>) -> Result<(), LevelError> {
+        let (w, h) = (self.w, self.h);
+        let point = |[x, y]: [i32; 2]| match 0 <= x && x < w && 0 <= y && y < h {
+            true => Ok(cube::Point::new(x, y)),
+            false => Err(LevelError::InvalidLocation { position: (x, y) }),
+        };
+        let plates = plates.into_iter().map(point).collect::<Result<_, _>>()?;
+        let doors = doors.into_iter().map(point).collect::<Result<_, _>>()?;
+        self.es.push(seed::Wire { plates, doors });
+        Ok(())
+    }
+
+    fn demand(&mut self, x: i32, y: i32, kind: cube::Kind) -> Result<(), LevelError> {
+        let o = cube::Point::new(x, y);
+        match self.ds.iter().position(|&d| d == o) {
+            Some(i) => {
+                self.dk.push((self.ds.remove(i), kind));
+                Ok(())
+            }
+            None => Err(LevelError::InvalidLocation { position: (x, y) }),
+        }
+    }
+
     fn bind_command(&mut self, x: i32, y: i32, command: seed::Command) -> Result<(), LevelError> {
         match self.m.get(x, y).and_then(|i| self.cs.get_mut(i)) {
             Some(x) => Ok(x.command = Some(command)),
@@ -344,38 +536,103 @@ impl LevelMapBuilder {
     }
 }
 
-struct CommandParser(seed::Command);
-
-impl Into<seed::Command> for CommandParser {
-    fn into(mut self) -> seed::Command {
-        self.0.movements.retain(|m| m.1 > 0);
-        self.0
+// Grammar of command strings:
+//
+//   sequence = { item }
+//   item     = ( action | "(" sequence ")" ) [ "~" ] [ number ]
+//   action   = "I" | "L" | "D" | "U" | "R" | "W"
+//
+// where "~" plays the item forth and then back, and "W" waits until the
+// player moves.
+struct CommandParser<'a>(std::iter::Peekable<std::str::Chars<'a>>);
+
+type Action = (Option<cube::Movement>, usize);
+
+impl<'a> CommandParser<'a> {
+    // groups are expanded, so the number of actions is limited.
+    const LIMIT: usize = 4096;
+    fn parse(content: &'a str, is_loop: bool) -> Result<seed::Command, LevelError> {
+        let mut parser = Self(content.chars().peekable());
+        let movements = parser.sequence()?;
+        match parser.0.next() {
+            Some(c) => Err(LevelError::InvalidMovement { character: c }),
+            None => Ok(seed::Command { is_loop, movements }),
+        }
     }
-}
 
-impl CommandParser {
-    fn new(is_loop: bool) -> Self {
-        Self(seed::Command {
-            is_loop,
-            movements: Vec::new(),
-        })
+    fn sequence(&mut self) -> Result<Vec<Action>, LevelError> {
+        let mut output = Vec::new();
+        while let Some(c) = self.0.next_if(|&c| c != ')') {
+            let mut item = match c {
+                '(' => {
+                    let inner = self.sequence()?;
+                    ensure!(self.0.next() == Some(')'), InvalidMovement { character: c });
+                    inner
+                }
+                _ => vec![Self::action(c)?],
+            };
+            if self.0.next_if_eq(&'~').is_some() {
+                let back = item
+                    .iter()
+                    .rev()
+                    .map(|&(m, t)| (m.map(|m| m.opposite()), t));
+                item = item.iter().cloned().chain(back).collect();
+            }
+            let times = self.number()?.unwrap_or(1);
+            match item[..] {
+                // long runs of a single movement stay in one action.
+                [(m, t)] if t > 0 && times > 0 => {
+                    let t = t
+                        .checked_mul(times)
+                        .ok_or_else(|| LevelError::InvalidCount {
+                            count: times.to_string(),
+                        })?;
+                    Self::push(&mut output, (m, t))
+                }
+                _ => {
+                    let size = item.len().checked_mul(times);
+                    let size = size.and_then(|size| size.checked_add(output.len()));
+                    ensure!(
+                        size.is_some_and(|size| size <= Self::LIMIT),
+                        TooLongCommand { limit: Self::LIMIT }
+                    );
+                    (0..times).for_each(|_| item.iter().for_each(|&a| Self::push(&mut output, a)))
+                }
+            }
+        }
+        Ok(output)
     }
 
-    fn put(&mut self, movement: Option<cube::Movement>) {
-        match self.0.movements.last_mut() {
-            Some(c) if c.0 == movement => c.1 += 1,
-            _ => self.0.movements.push((movement, 1)),
+    fn push(output: &mut Vec<Action>, (movement, times): Action) {
+        match output.last_mut() {
+            Some(c) if c.0 == movement && c.1 > 0 && times > 0 => c.1 += times,
+            _ => output.push((movement, times)),
         }
     }
 
-    fn add(&mut self, number: i32) {
-        match self.0.movements.last_mut() {
-            Some(c) => c.1 = c.1 + number as usize - 1,
-            _ => self.0.movements.push((None, number as usize)),
+    fn action(c: char) -> Result<Action, LevelError> {
+        match c {
+            'I' => Ok((None, 1)),
+            'L' => Ok((Some(cube::Movement::Left), 1)),
+            'D' => Ok((Some(cube::Movement::Down), 1)),
+            'U' => Ok((Some(cube::Movement::Up), 1)),
+            'R' => Ok((Some(cube::Movement::Right), 1)),
+            'W' => Ok((None, 0)),
+            _ => Err(LevelError::InvalidMovement { character: c }),
         }
     }
 
-    fn is_empty(&self) -> bool {
-        self.0.movements.is_empty()
+    fn number(&mut self) -> Result<Option<usize>, LevelError> {
+        let mut digits = String::new();
+        while let Some(c) = self.0.next_if(char::is_ascii_digit) {
+            digits.push(c);
+        }
+        match digits.is_empty() {
+            true => Ok(None),
+            false => match digits.parse() {
+                Ok(count) => Ok(Some(count)),
+                Err(_) => Err(LevelError::InvalidCount { count: digits }),
+            },
+        }
     }
 }
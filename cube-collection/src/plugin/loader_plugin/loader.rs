@@ -74,3 +74,163 @@ impl AssetLoader for SeedsAssetLoader {
         &["toml"]
     }
 }
+
+/////////////////////////////////////////////////////////////////////////////
+// tests
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::read_to_string, path::Path};
+
+    use cube_core::{
+        cube::{Floor, Kind, Movement, Point, RuleSet},
+        solver::{solve, Solution},
+    };
+
+    use super::{super::LevelSource, LevelIndex};
+
+    #[test]
+    #[ignore = "it searches millions of states and takes minutes"]
+    fn all_levels_solvable() {
+        // states to search, which is enough for every level except those
+        // expected to be too large.
+        const LIMIT: usize = 2_000_000;
+
+        // moves of the solution with the fewest turns, or none if a level
+        // has too many states to search.
+        const EXPECTED: &[(&str, Option<usize>)] = &[
+            ("introduction", Some(10)),
+            ("rotation", Some(15)),
+            ("merge", Some(26)),
+            ("detour", Some(30)),
+            ("tetris", Some(14)),
+            ("matrix", None),
+            ("haircut", Some(17)),
+            ("printer", Some(16)),
+            ("moving-together", Some(13)),
+            ("moving-forest", Some(39)),
+            ("centrosymmetry-and-axisymmetry", Some(20)),
+            ("swap", Some(13)),
+            ("sacrifice", Some(17)),
+            ("reincarnation", Some(8)),
+            ("triangle-power", Some(11)),
+            ("synchronization", Some(14)),
+            ("now-or-never", Some(28)),
+            ("unlock", Some(39)),
+        ];
+
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let index = read_to_string(root.join("level/index.toml")).unwrap();
+        let index = toml::from_str::<LevelIndex>(&index).unwrap();
+        let folder = root.join(&index.directory);
+        assert_eq!(index.name_list.len(), EXPECTED.len());
+        for name in index.name_list {
+            let expected = EXPECTED.iter().find(|(o, _)| *o == name);
+            let Some(&(_, expected)) = expected else {
+                panic!("{} has no expected moves", name);
+            };
+            let path = folder.join([&name, ".", &index.extension].concat());
+            let text = read_to_string(path).unwrap();
+            let seed = toml::from_str::<LevelSource>(&text)
+                .unwrap()
+                .into_seed()
+                .unwrap();
+            let Some(expected) = expected else {
+                continue;
+            };
+
+            match solve(&seed, &RuleSet::default(), LIMIT) {
+                Solution::Found(inputs) => {
+                    let moves = inputs.iter().filter(|input| !input.is_empty()).count();
+                    assert_eq!(moves, expected, "moves of {}", name);
+                }
+                Solution::Exhausted => panic!("{} has more than {} states", name, LIMIT),
+                Solution::Unsolvable => panic!("{} is unsolvable", name),
+            }
+        }
+    }
+
+    #[test]
+    fn demands() {
+        let parse = |binding: &str| {
+            let text = format!(
+                "[info]\ntitle = 't'\nauthor = 'a'\n[map]\nraw = 'rxx'\n\
+                 [[map.demands]]\nkind = 'R'\nbinding = {}\n",
+                binding
+            );
+            toml::from_str::<LevelSource>(&text).unwrap().into_seed()
+        };
+
+        // lowercase letters are cubes, and targets demand kinds by bindings.
+        let seed = parse("[[2, 0]]").unwrap();
+        assert_eq!(seed.cubes.len(), 1);
+        assert_eq!(seed.destnations, vec![Point::new(1, 0)]);
+        assert_eq!(seed.demands, vec![(Point::new(2, 0), Kind::Red)]);
+
+        // and bindings must be targets.
+        assert!(parse("[[0, 0]]").is_err());
+        assert!(parse("[[3, 0]]").is_err());
+    }
+
+    #[test]
+    fn gates() {
+        let text = "[info]\ntitle = 't'\nauthor = 'a'\n[map]\nraw = 'G←↓↑→<v^>'\n";
+        let seed = toml::from_str::<LevelSource>(text)
+            .unwrap()
+            .into_seed()
+            .unwrap();
+        let (l, d, u, r) = (
+            Movement::Left,
+            Movement::Down,
+            Movement::Up,
+            Movement::Right,
+        );
+        let floors = seed.floors.into_iter().map(|(_, floor)| floor);
+        let gates = [l, d, u, r].map(Floor::Gate);
+        let conveyors = [l, d, u, r].map(Floor::Conveyor);
+        assert_eq!(floors.collect::<Vec<_>>(), [gates, conveyors].concat());
+    }
+
+    #[test]
+    fn commands() {
+        let parse = |content: &str| {
+            let text = format!(
+                "[info]\ntitle = 't'\nauthor = 'a'\n[map]\nraw = 'R'\n\
+                 [[map.commands]]\nbinding = [[0, 0]]\ncontent = '{}'\nlooping = false\n",
+                content
+            );
+            let seed = toml::from_str::<LevelSource>(&text).unwrap().into_seed();
+            seed.map(|mut seed| seed.cubes.remove(0).command.unwrap().movements)
+        };
+        let (l, d, u, r) = (
+            Some(Movement::Left),
+            Some(Movement::Down),
+            Some(Movement::Up),
+            Some(Movement::Right),
+        );
+
+        assert_eq!(
+            parse("R6I2L6I2").unwrap(),
+            vec![(r, 6), (None, 2), (l, 6), (None, 2)]
+        );
+        assert_eq!(
+            parse("(LR)2").unwrap(),
+            vec![(l, 1), (r, 1), (l, 1), (r, 1)]
+        );
+        assert_eq!(
+            parse("(R2U)~").unwrap(),
+            vec![(r, 2), (u, 1), (d, 1), (l, 2)]
+        );
+        assert_eq!(parse("RWR").unwrap(), vec![(r, 1), (None, 0), (r, 1)]);
+        assert!(parse("(R").is_err());
+        assert!(parse("R)").is_err());
+        assert!(parse("3R").is_err());
+
+        // counts neither overflow nor expand without limit.
+        assert!(parse("R99999999999999999999999").is_err());
+        assert!(parse("(R2)9999999999999999999").is_err());
+        assert!(parse("(LR)99999999").is_err());
+        assert!(parse("((LR)2048)2").is_err());
+        assert_eq!(parse("(LR)2048").unwrap().len(), 4096);
+    }
+}
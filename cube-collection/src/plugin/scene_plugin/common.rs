@@ -1,5 +1,9 @@
 mod adaption;
+mod door;
+mod hint;
 mod marker;
+mod prompt;
+mod score;
 mod state;
 mod style;
 mod translate;
@@ -9,6 +13,10 @@ pub mod bundle;
 pub mod system {
     use super::*;
     pub use adaption::self_adaption_system as self_adaption;
+    pub use door::door_system as door;
+    pub use hint::hint_system as hint;
+    pub use prompt::prompt_system as prompt;
+    pub use score::score_system as score;
     pub use state::state_system as state;
     pub use translate::{
         position_system as position, realpha_system as realpha, recolor_system as recolor,
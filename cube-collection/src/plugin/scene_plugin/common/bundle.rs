@@ -3,12 +3,15 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy_prototype_lyon::entity::ShapeBundle;
 use bevy_prototype_lyon::prelude::*;
-use cube_core::cube::{Constraint, Kind, Movement, Neighborhood, Point};
+use cube_core::cube::{Constraint, Floor, Kind, Movement, Neighborhood, Point};
 
 use super::{
     super::{model::World, view::ViewMapper},
     adaption::AutoRescale,
+    door::Door,
     marker::Earthbound,
+    prompt::Prompt,
+    score::Scoreboard,
     style::{self, BoundaryBuilder},
     translate::TranslateAlpha,
 };
@@ -39,6 +42,14 @@ struct CubeBundle {
     color: Fill,
 }
 
+#[derive(Bundle)]
+struct WallBundle {
+    bound: Earthbound,
+    scale: AutoRescale,
+    shape: ShapeBundle,
+    color: Fill,
+}
+
 #[derive(Bundle)]
 pub struct FloorBundle {
     bound: Earthbound,
@@ -67,7 +78,11 @@ pub fn hello_world(commands: &mut Commands, state: &World, mapper: &ViewMapper)
 
     // create destinations
     let delta = mapper.scale(&(0.5, 0.5));
-    for goal in state.goals() {
+    for (goal, kind) in state.goals() {
+        let color = match kind {
+            None => style::destnation_color(),
+            Some(kind) => style::demand_color(kind),
+        };
         commands
             .spawn(DestinationBundle {
                 bound: Earthbound,
@@ -83,7 +98,7 @@ pub fn hello_world(commands: &mut Commands, state: &World, mapper: &ViewMapper)
                     (mapper.locate(&goal) + delta).extend(2.),
                     Vec3::new(scale, scale, 0.),
                 ),
-                color: Fill::color(style::destnation_color()),
+                color: Fill::color(color),
             })
             .insert(TranslateAlpha::new(0.1, 0.4, Duration::from_secs(4)));
     }
@@ -117,6 +132,125 @@ pub fn hello_world(commands: &mut Commands, state: &World, mapper: &ViewMapper)
         });
     }
 
+    // create floor tiles
+    for (point, floor) in state.floors() {
+        commands.spawn(FloorBundle {
+            bound: Earthbound,
+            scale: AutoRescale { point, offset: 0.5 },
+            shape: make_shape(
+                &shapes::Polygon {
+                    points: style::cube_boundaries(Neighborhood::new(), 0.95),
+                    closed: true,
+                },
+                (mapper.locate(&point) + delta).extend(0.5),
+                Vec3::new(scale, scale, 0.),
+            ),
+            color: Fill::color(style::floor_tile_color(floor)),
+        });
+
+        if let Floor::Conveyor(movement) | Floor::Gate(movement) = floor {
+            commands.spawn(FloorBundle {
+                bound: Earthbound,
+                scale: AutoRescale { point, offset: 0.5 },
+                shape: make_shape(
+                    &shapes::Polygon {
+                        points: style::arrow_points(movement),
+                        closed: true,
+                    },
+                    (mapper.locate(&point) + delta).extend(0.6),
+                    Vec3::new(scale, scale, 0.),
+                ),
+                color: Fill::color(style::floor_color()),
+            });
+        }
+    }
+
+    // create plates and doors
+    for point in state.plates() {
+        commands.spawn(FloorBundle {
+            bound: Earthbound,
+            scale: AutoRescale { point, offset: 0.5 },
+            shape: make_shape(
+                &shapes::Polygon {
+                    points: style::cube_boundaries(Neighborhood::new(), 0.6),
+                    closed: true,
+                },
+                (mapper.locate(&point) + delta).extend(0.5),
+                Vec3::new(scale, scale, 0.),
+            ),
+            color: Fill::color(style::plate_color()),
+        });
+    }
+    for (point, open) in state.doors() {
+        let mut color = style::door_color();
+        color.set_alpha(style::door_alpha(open));
+        commands.spawn((
+            Door(point),
+            FloorBundle {
+                bound: Earthbound,
+                scale: AutoRescale { point, offset: 0.5 },
+                shape: make_shape(
+                    &shapes::Polygon {
+                        points: style::cube_boundaries(Neighborhood::new(), 0.95),
+                        closed: true,
+                    },
+                    (mapper.locate(&point) + delta).extend(0.7),
+                    Vec3::new(scale, scale, 0.),
+                ),
+                color: Fill::color(color),
+            },
+        ));
+    }
+
+    // create walls
+    for (point, neighborhood) in state.walls() {
+        boundary_builder.put(point, neighborhood);
+        commands.spawn(WallBundle {
+            bound: Earthbound,
+            scale: AutoRescale { point, offset: 0.5 },
+            shape: make_shape(
+                &shapes::Polygon {
+                    points: style::cube_boundaries(neighborhood, 0.95),
+                    closed: true,
+                },
+                (mapper.locate(&point) + delta).extend(1.),
+                Vec3::new(scale, scale, 1.),
+            ),
+            color: Fill::color(style::wall_color()),
+        });
+    }
+
+    // create scoreboard
+    commands.spawn((
+        Scoreboard,
+        Earthbound,
+        Text::new(""),
+        TextFont::from_font_size(20.),
+        TextColor(style::text_color()),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            left: Val::Px(8.),
+            ..default()
+        },
+    ));
+
+    // create prompt of dead ends
+    commands.spawn((
+        Prompt,
+        Earthbound,
+        Text::new("No way out. Press R to restart, or Z to undo."),
+        TextFont::from_font_size(20.),
+        TextColor(style::text_color()),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.),
+            left: Val::Px(8.),
+            ..default()
+        },
+        Visibility::Hidden,
+    ));
+
     // create floor
     let bottom_left = Point::new(0, 0);
     commands.spawn(FloorBundle {
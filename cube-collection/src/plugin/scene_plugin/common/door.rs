@@ -0,0 +1,21 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use cube_core::cube::Point;
+
+use super::{super::model::World, style};
+
+/// Door marks a door tile, which fades out while it is open.
+#[derive(Component)]
+pub struct Door(pub Point);
+
+pub fn door_system(world: Res<World>, mut query: Query<(&Door, &mut Fill)>) {
+    let open = (world.doors())
+        .filter_map(|(point, open)| open.then_some(point))
+        .collect::<HashSet<_>>();
+    for (door, mut fill) in &mut query {
+        fill.color
+            .set_alpha(style::door_alpha(open.contains(&door.0)));
+    }
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, Task};
+use bevy_prototype_lyon::prelude::*;
+use cube_core::cube::{Input, Point};
+
+use super::{
+    super::{input::HintRequested, model::World, view::GridView},
+    adaption::AutoRescale,
+    marker::Earthbound,
+    style,
+};
+
+/// Hint marks an arrow that suggests the next movement of a cube, which fades
+/// out and disappears in a while.
+#[derive(Component)]
+pub struct Hint(Timer);
+
+// where players are when a search starts, and the search.
+type Pending = (Vec<(usize, Point)>, Task<Option<Input>>);
+
+pub fn hint_system(
+    mut commands: Commands,
+    mut requests: EventReader<HintRequested>,
+    mut pending: Local<Option<Pending>>,
+    mut arrows: Query<(Entity, &mut Hint, &mut Fill)>,
+    world: Res<World>,
+    view: Res<GridView>,
+    time: Res<Time>,
+) {
+    // fade out old arrows
+    let delta = time.delta();
+    for (id, mut hint, mut fill) in &mut arrows {
+        if hint.0.tick(delta).finished() {
+            commands.entity(id).despawn();
+        } else {
+            let alpha = style::hint_color().alpha() * hint.0.fraction_remaining();
+            fill.color.set_alpha(alpha);
+        }
+    }
+
+    // a search for another level is useless, and dropping it cancels it
+    if world.is_added() {
+        *pending = None;
+    }
+
+    // start searching, but only one at a time
+    if requests.read().count() > 0 && pending.is_none() {
+        *pending = Some((players(&world), world.hint()));
+    }
+
+    // show the result if players are still where it is for, even if other
+    // cubes have moved by commands
+    let input = match pending.as_mut() {
+        None => return,
+        Some((_, task)) => match block_on(poll_once(task)) {
+            None => return,
+            Some(input) => input,
+        },
+    };
+    let source = pending.take().map(|(source, _)| source);
+    let input = match input {
+        Some(input) if source == Some(players(&world)) => input,
+        _ => return,
+    };
+
+    let mapper = view.mapping();
+    let scale = mapper.unit();
+    let delta = mapper.scale(&(0.5, 0.5));
+    let arrows = (world.cubes())
+        .filter(|unit| world.controlled(unit.kind))
+        .filter_map(|unit| Some((input.get(unit.kind)?, unit)));
+    for (movement, unit) in arrows {
+        let shape = shapes::Polygon {
+            points: style::arrow_points(movement),
+            closed: true,
+        };
+        commands.spawn((
+            Hint(Timer::new(Duration::from_secs(1), TimerMode::Once)),
+            Earthbound,
+            AutoRescale {
+                point: unit.position,
+                offset: 0.5,
+            },
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shape),
+                transform: Transform {
+                    translation: (mapper.locate(&unit.position) + delta).extend(3.),
+                    scale: Vec3::new(scale, scale, 1.),
+                    ..default()
+                },
+                ..default()
+            },
+            Fill::color(style::hint_color()),
+        ));
+    }
+}
+
+fn players(world: &World) -> Vec<(usize, Point)> {
+    (world.cubes())
+        .filter(|unit| world.controlled(unit.kind))
+        .map(|unit| (unit.id, unit.position))
+        .collect()
+}
@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+use super::super::model::World;
+
+/// Prompt marks the text that asks to restart a level from a dead end.
+#[derive(Component, Default)]
+pub struct Prompt;
+
+pub fn prompt_system(mut world: ResMut<World>, mut query: Query<&mut Visibility, With<Prompt>>) {
+    world.bypass_change_detection().settle();
+    let next = match world.stuck() {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    };
+    for mut visibility in &mut query {
+        visibility.set_if_neq(next);
+    }
+}
@@ -0,0 +1,23 @@
+use bevy::prelude::*;
+
+use super::super::model::World;
+
+/// Scoreboard marks the text that shows moves of the current level.
+#[derive(Component, Default)]
+pub struct Scoreboard;
+
+pub fn score_system(world: Res<World>, mut query: Query<&mut Text, With<Scoreboard>>) {
+    let mut text = format!("Moves: {}", world.moves());
+    if let Some(par) = world.par() {
+        text += &format!("  Par: {}", par);
+    }
+    if let Some(max) = world.max_moves() {
+        text += &format!("  Limit: {}", max);
+    }
+
+    for mut board in &mut query {
+        if board.0 != text {
+            board.0.clone_from(&text);
+        }
+    }
+}
@@ -1,7 +1,14 @@
+use std::collections::VecDeque;
+
 use bevy::prelude::*;
+use cube_core::objective::Outcome;
 
 use super::{
-    super::{input::MovementChanged, model::World, scene_running::WorldChanged},
+    super::{
+        input::{HistoryChanged, MovementChange, MovementChanged},
+        model::World,
+        scene_running::WorldChanged,
+    },
     adaption::AutoRescale,
     bundle::Cubic,
     translate::{TranslateColor, TranslatePosition, TranslateShape},
@@ -10,41 +17,71 @@ use super::{
 pub fn state_system(
     mut commands: Commands,
     mut input_action: EventReader<MovementChanged>,
+    mut input_history: EventReader<HistoryChanged>,
     mut change_world: EventWriter<WorldChanged>,
     mut query: Query<(Entity, &mut Cubic, &mut AutoRescale)>,
     mut world: ResMut<World>,
     mut ticker: Local<detail::Ticker>,
-    mut actions: Local<detail::ActionQueue>,
-    mut completed: Local<bool>,
+    mut actions: Local<[detail::ActionQueue; 2]>,
+    mut rewinds: Local<VecDeque<HistoryChanged>>,
+    mut finished: Local<Option<Outcome>>,
+    mut paused: Local<bool>,
     time: Res<Time>,
 ) {
     // update actions
     for action in input_action.read() {
-        use MovementChanged::*;
-        match action {
-            Add(m) => actions.add(*m),
-            Set(m) => actions.set(*m),
+        use MovementChange::*;
+        let actions = &mut actions[action.layout];
+        match action.change {
+            Add(m) => actions.add(m),
+            Set(m) => actions.set(m),
         };
     }
 
+    // update history actions
+    rewinds.extend(input_history.read());
+    if world.is_added() {
+        *paused = false;
+    }
+
     // update world
     let step = world.step();
     let delta = time.delta();
     let diffs = match ticker.tick(delta) {
         false => return, // skip
-        true => world.next(actions.pop()),
+        true => match rewinds.pop_front() {
+            Some(HistoryChanged::Undo) => {
+                *paused = true;
+                world.undo()
+            }
+            Some(HistoryChanged::Redo) => {
+                *paused = true;
+                world.redo()
+            }
+            None => {
+                // time stands still after undo or redo until the next move.
+                let layouts = actions.each_mut().map(|actions| actions.pop());
+                if *paused && layouts.iter().all(Option::is_none) {
+                    return;
+                }
+                *paused = false;
+                world.next(layouts)
+            }
+        },
     };
 
-    if *completed {
-        // delay one round to move to next level
-        *completed = false;
-
+    if let Some(outcome) = finished.take() {
+        // delay one round to move to next level or restart, and
         // avoid current states affecting next level
-        actions.reset();
+        actions.iter_mut().for_each(|actions| actions.reset());
+        rewinds.clear();
         ticker.reset();
 
         // report level change event
-        change_world.send(WorldChanged::Next);
+        change_world.send(match outcome {
+            Outcome::Failed => WorldChanged::Restart,
+            _ => WorldChanged::Next,
+        });
 
         // avoid update completed again
         return;
@@ -84,10 +121,10 @@ pub fn state_system(
                 cube.constraint = value;
             }
         }
-
-        // check status
-        *completed = world.done();
     }
+
+    // check status, even if nothing moves as turns still count
+    *finished = Some(world.outcome()).filter(|&o| o != Outcome::Playing);
 }
 
 mod detail {
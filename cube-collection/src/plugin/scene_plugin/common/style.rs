@@ -1,6 +1,6 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
-use cube_core::cube::{Adjacence, Kind, Movement, Neighborhood, Point};
+use cube_core::cube::{Adjacence, Floor, Kind, Movement, Neighborhood, Point};
 
 pub const fn background_color() -> Color {
     Color::Srgba(css::WHITE)
@@ -10,16 +10,59 @@ pub const fn floor_color() -> Color {
     Color::srgb(0.2, 0.2, 0.2)
 }
 
+pub const fn floor_tile_color(floor: Floor) -> Color {
+    match floor {
+        Floor::Ice => Color::srgb(0.35, 0.45, 0.55),
+        Floor::Conveyor(_) => Color::srgb(0.35, 0.35, 0.35),
+        Floor::Portal(_) => Color::srgb(0.50, 0.40, 0.60),
+        Floor::Gate(_) => Color::srgb(0.30, 0.50, 0.40),
+    }
+}
+
+pub const fn plate_color() -> Color {
+    Color::srgb(0.65, 0.55, 0.35)
+}
+
+pub const fn door_color() -> Color {
+    Color::srgb(0.65, 0.55, 0.35)
+}
+
+pub const fn door_alpha(open: bool) -> f32 {
+    match open {
+        true => 0.15,
+        false => 1.0,
+    }
+}
+
+pub const fn wall_color() -> Color {
+    Color::srgb(0.6, 0.6, 0.6)
+}
+
+pub const fn text_color() -> Color {
+    Color::srgb(0.2, 0.2, 0.2)
+}
+
+pub const fn hint_color() -> Color {
+    Color::srgba(0.2, 0.2, 0.2, 0.8)
+}
+
 pub const fn destnation_color() -> Color {
     Color::Srgba(css::GRAY)
 }
 
+pub fn demand_color(kind: Kind) -> Color {
+    // a little darker than the cube, so it is still visible when covered.
+    cube_color(kind).darker(0.2)
+}
+
 pub const fn cube_color(kind: Kind) -> Color {
     match kind {
         Kind::White /* **/ => Color::srgb(1.000, 1.000, 1.000),
         Kind::Red /*   **/ => Color::srgb(0.988, 0.512, 0.512),
         Kind::Blue /*  **/ => Color::srgb(0.582, 0.727, 0.945),
         Kind::Green /* **/ => Color::srgb(0.533, 0.859, 0.425),
+        Kind::Yellow /**/ => Color::srgb(0.965, 0.827, 0.396),
+        Kind::Purple /**/ => Color::srgb(0.765, 0.580, 0.902),
     }
 }
 
@@ -92,6 +135,26 @@ pub fn cube_boundaries(pattern: Neighborhood, scale: f32) -> Vec<Vec2> {
     points
 }
 
+pub fn arrow_points(movement: Movement) -> Vec<Vec2> {
+    // an upward triangle in a unit square centered at (0, 0).
+    let (tip, left, right) = (
+        Vec2::new(0., 0.3),
+        Vec2::new(-0.25, -0.2),
+        Vec2::new(0.25, -0.2),
+    );
+    let rotate = Vec2::from(match movement {
+        Movement::Left /*  **/ => (0., 1.),
+        Movement::Down /*  **/ => (-1., 0.),
+        Movement::Up /*    **/ => (1., 0.),
+        Movement::Right /* **/ => (0., -1.),
+    });
+    vec![
+        rotate.rotate(tip),
+        rotate.rotate(left),
+        rotate.rotate(right),
+    ]
+}
+
 pub struct BoundaryBuilder {
     width: usize,
     height: usize,
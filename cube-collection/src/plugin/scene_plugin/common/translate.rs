@@ -100,7 +100,10 @@ impl TranslatePosition {
         if let Some(target) = diff.position {
             return Some(TranslatePosition {
                 elapse: Timer::new(duration, TimerMode::Once),
-                parameters: Position::Move(position, target),
+                parameters: match diff.warp {
+                    true => Position::Warp(position, target),
+                    false => Position::Move(position, target),
+                },
             });
         }
 
@@ -130,6 +133,7 @@ impl TranslatePosition {
 #[derive(Debug)]
 enum Position {
     Move(Point, Point),      // (from, to)
+    Warp(Point, Point),      // (from, to)
     Spin(Point, Point, f32), // (from, delta, limit)
     Stop(Point),             // (from)
 }
@@ -154,6 +158,11 @@ pub fn position_system(
                     transform.translation = locate(&to).extend(z);
                     commands.entity(id).remove::<TranslatePosition>();
                 }
+                Warp(_, to) => {
+                    transform.translation = locate(&to).extend(z);
+                    transform.scale = Vec3::new(mapper.unit(), mapper.unit(), 1.);
+                    commands.entity(id).remove::<TranslatePosition>();
+                }
                 Spin(from, _, _) => {
                     transform.translation = locate(&from).extend(z);
                 }
@@ -171,6 +180,17 @@ pub fn position_system(
                     let current = source + (target - source) * percent;
                     transform.translation = current.extend(z);
                 }
+                Warp(from, to) => {
+                    // shrink at the source and then grow at the target.
+                    let percent = translate.elapse.fraction();
+                    let (point, size) = match percent < 0.5 {
+                        true => (from, 1. - 2. * percent),
+                        false => (to, 2. * percent - 1.),
+                    };
+                    let size = mapper.unit() * size;
+                    transform.translation = locate(&point).extend(z);
+                    transform.scale = Vec3::new(size, size, 1.);
+                }
                 Spin(from, delta, limit) => {
                     let percent = translate.elapse.fraction();
                     let percent = (1.0 - percent).min(percent).min(limit);
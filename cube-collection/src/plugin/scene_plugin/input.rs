@@ -6,18 +6,28 @@ use super::{scene_loading::HardReset, scene_running::WorldChanged};
 
 pub fn setup(app: &mut App, state: impl States) {
     app.add_event::<MovementChanged>()
+        .add_event::<HistoryChanged>()
+        .add_event::<HintRequested>()
         .add_systems(PreUpdate, keyboard.run_if(in_state(state)));
 }
 
+/// A change of movement from one of the key layouts, which are `W`/`A`/`S`/`D`
+/// and arrow keys.
 #[derive(Clone, Debug, Event, PartialEq, Eq)]
-pub enum MovementChanged {
+pub struct MovementChanged {
+    pub layout: usize,
+    pub change: MovementChange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MovementChange {
     Add(Movement),
     Set(Option<Movement>),
 }
 
-impl MovementChanged {
+impl MovementChange {
     fn cover(&self, that: &Self) -> bool {
-        use MovementChanged::*;
+        use MovementChange::*;
         match self {
             _ if self == that => true,
             Add(_) => false,
@@ -26,7 +36,16 @@ impl MovementChanged {
     }
 }
 
-impl Default for MovementChanged {
+#[derive(Clone, Copy, Debug, Event, PartialEq, Eq)]
+pub enum HistoryChanged {
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Copy, Debug, Event)]
+pub struct HintRequested;
+
+impl Default for MovementChange {
     fn default() -> Self {
         Self::Set(None)
     }
@@ -36,6 +55,8 @@ impl Default for MovementChanged {
 enum Command {
     Reset,
     Control(WorldChanged),
+    History(HistoryChanged),
+    Hint,
     Movement(MovementChanged),
     #[default]
     DoNothing,
@@ -46,8 +67,10 @@ fn keyboard(
     mut input: EventReader<KeyboardInput>,
     mut change_world: EventWriter<WorldChanged>,
     mut change_movement: EventWriter<MovementChanged>,
+    mut change_history: EventWriter<HistoryChanged>,
+    mut request_hint: EventWriter<HintRequested>,
     mut trgger_reload: EventWriter<HardReset>,
-    mut actions: Local<ActionSequence>,
+    mut actions: Local<[ActionSequence; 2]>,
 ) {
     // try to calculate a command and send it to movement system.
     for key in input.read() {
@@ -60,12 +83,19 @@ fn keyboard(
             KeyCode::KeyR if presse => Command::Control(WorldChanged::Restart),
             KeyCode::KeyN if presse => Command::Control(WorldChanged::Next),
             KeyCode::KeyL if presse => Command::Control(WorldChanged::Last),
+            KeyCode::KeyZ if presse && shift => Command::History(HistoryChanged::Redo),
+            KeyCode::KeyZ if presse => Command::History(HistoryChanged::Undo),
+            KeyCode::KeyH if presse => Command::Hint,
 
             // movement
-            KeyCode::KeyW | KeyCode::ArrowUp => actions.input(Movement::Up, presse),
-            KeyCode::KeyA | KeyCode::ArrowLeft => actions.input(Movement::Left, presse),
-            KeyCode::KeyS | KeyCode::ArrowDown => actions.input(Movement::Down, presse),
-            KeyCode::KeyD | KeyCode::ArrowRight => actions.input(Movement::Right, presse),
+            KeyCode::KeyW => actions[0].input(0, Movement::Up, presse),
+            KeyCode::KeyA => actions[0].input(0, Movement::Left, presse),
+            KeyCode::KeyS => actions[0].input(0, Movement::Down, presse),
+            KeyCode::KeyD => actions[0].input(0, Movement::Right, presse),
+            KeyCode::ArrowUp => actions[1].input(1, Movement::Up, presse),
+            KeyCode::ArrowLeft => actions[1].input(1, Movement::Left, presse),
+            KeyCode::ArrowDown => actions[1].input(1, Movement::Down, presse),
+            KeyCode::ArrowRight => actions[1].input(1, Movement::Right, presse),
 
             // ignore
             _ => Command::DoNothing,
@@ -78,6 +108,12 @@ fn keyboard(
             Command::Control(control) => {
                 change_world.send(control);
             }
+            Command::History(history) => {
+                change_history.send(history);
+            }
+            Command::Hint => {
+                request_hint.send(HintRequested);
+            }
             Command::Movement(movement) => {
                 change_movement.send(movement);
             }
@@ -90,19 +126,20 @@ fn keyboard(
 // action sequence
 
 #[derive(Default /* required by Local */)]
-struct ActionSequence(Vec<Movement>, MovementChanged);
+struct ActionSequence(Vec<Movement>, MovementChange);
 impl ActionSequence {
-    fn input(&mut self, movement: Movement, pressed: bool) -> Command {
+    fn input(&mut self, layout: usize, movement: Movement, pressed: bool) -> Command {
         let next = self.update(movement, pressed);
         if !self.1.cover(&next) {
             self.1 = next;
-            Command::Movement(self.1.clone())
+            let change = self.1.clone();
+            Command::Movement(MovementChanged { layout, change })
         } else {
             Command::DoNothing
         }
     }
 
-    fn update(&mut self, movement: Movement, pressed: bool) -> MovementChanged {
+    fn update(&mut self, movement: Movement, pressed: bool) -> MovementChange {
         self.0.retain(|&m| m != movement);
         if pressed {
             self.0.push(movement);
@@ -110,11 +147,11 @@ impl ActionSequence {
 
         let (conflic, movement) = self.evaluate();
         if conflic {
-            MovementChanged::Set(movement)
+            MovementChange::Set(movement)
         } else if let Some(movement) = movement {
-            MovementChanged::Add(movement)
+            MovementChange::Add(movement)
         } else {
-            MovementChanged::Set(None)
+            MovementChange::Set(None)
         }
     }
 
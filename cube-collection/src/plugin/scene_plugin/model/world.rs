@@ -1,9 +1,14 @@
-use std::{collections::HashMap, time::Duration};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::Duration,
+};
 
 use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
 use bevy::time::Timer;
 use cube_core::{
-    cube::{Movement, Point},
+    cube::{Floor, Input, Kind, Movement, Neighborhood, Point, RuleSet},
+    objective::Outcome,
     seed::Seed,
     Diff, Unit,
 };
@@ -12,38 +17,143 @@ use cube_core::{
 pub struct World {
     state: cube_core::CubeCore,
     timer: Timer,
+    par: Option<usize>,
+    max_moves: Option<usize>,
+    players: Vec<Kind>,
+    stuck: bool,
+    search: Option<Task<bool>>,
 }
 
 impl World {
+    // turns to undo, about half an hour of play at the default pace.
+    const HISTORY_LIMIT: usize = 10_000;
+    // states to search for a way out, which may take a while in background.
+    const SEARCH_LIMIT: usize = 2000;
+
     pub fn new(seed: &Seed) -> Self {
+        let mut state = cube_core::CubeCore::new(seed, &RuleSet::default());
+        state.set_history_limit(Some(Self::HISTORY_LIMIT));
         Self {
-            state: cube_core::CubeCore::new(&seed),
+            state,
             timer: Timer::new(Duration::from_millis(200), TimerMode::Repeating),
+            par: seed.info.par,
+            max_moves: seed.info.max_moves,
+            players: seed.players.clone(),
+            stuck: false,
+            search: None,
+        }
+    }
+
+    /// Play one turn with movements of key layouts. Each layout steers one of
+    /// the players if there are more than one, or else any layout steers all.
+    pub fn next(&mut self, layouts: [Option<Movement>; 2]) -> HashMap<usize, Diff> {
+        let input = match self.players.as_slice() {
+            [] | [_] => Input::from(layouts[0].or(layouts[1])),
+            players => (players.iter().zip(layouts))
+                .fold(Input::new(), |input, (&kind, m)| input.set(kind, m)),
+        };
+        let diffs = Self::collect(self.state.commit(input));
+        self.review(&diffs);
+        diffs
+    }
+
+    /// Step back to the state before the last move, over idle turns.
+    pub fn undo(&mut self) -> HashMap<usize, Diff> {
+        let moves = self.state.moves();
+        let mut diffs = HashMap::new();
+        while self.state.history_len() > 0 && self.state.moves() == moves {
+            Self::merge(&mut diffs, self.state.undo());
+        }
+        self.review(&diffs);
+        diffs
+    }
+
+    /// Replay turns that have been undone until the next move.
+    pub fn redo(&mut self) -> HashMap<usize, Diff> {
+        let moves = self.state.moves();
+        let mut diffs = HashMap::new();
+        loop {
+            let len = self.state.history_len();
+            Self::merge(&mut diffs, self.state.redo());
+            if self.state.history_len() == len || self.state.moves() != moves {
+                break;
+            }
         }
+        self.review(&diffs);
+        diffs
     }
 
-    pub fn next(&mut self, movement: Option<Movement>) -> HashMap<usize, Diff> {
-        self.state
-            .commit(movement)
-            .map(|diff| (diff.id, diff))
-            .collect::<HashMap<_, _, _>>()
-            .into()
+    /// Search for a hint of the next input without blocking.
+    pub fn hint(&self) -> Task<Option<Input>> {
+        let state = self.state.present();
+        AsyncComputeTaskPool::get().spawn(async move { state.hint() })
+    }
+
+    /// Whether cubes of the kind are steered by players.
+    pub fn controlled(&self, kind: Kind) -> bool {
+        self.state.controlled(kind)
+    }
+
+    /// Whether the level is known to be unsolvable from now on.
+    pub fn stuck(&self) -> bool {
+        self.stuck
+    }
+
+    /// Take the result of the search for a dead end if it is done.
+    pub fn settle(&mut self) {
+        let stuck = match self.search.as_mut() {
+            None => return,
+            Some(task) => match block_on(poll_once(task)) {
+                None => return,
+                Some(stuck) => stuck,
+            },
+        };
+        self.stuck = stuck;
+        self.search = None;
     }
 
     pub fn cubes(&self) -> impl Iterator<Item = Unit> + '_ {
         self.state.iter()
     }
 
-    pub fn goals(&self) -> impl Iterator<Item = Point> + '_ {
-        self.state.goals().map(|(point, _)| point)
+    pub fn walls(&self) -> impl Iterator<Item = (Point, Neighborhood)> + '_ {
+        self.state.walls()
+    }
+
+    pub fn floors(&self) -> impl Iterator<Item = (Point, Floor)> + '_ {
+        self.state.floors()
+    }
+
+    pub fn plates(&self) -> impl Iterator<Item = Point> + '_ {
+        self.state.plates()
+    }
+
+    pub fn doors(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
+        self.state.doors()
+    }
+
+    pub fn goals(&self) -> impl Iterator<Item = (Point, Option<Kind>)> + '_ {
+        self.state.goals().map(|(point, kind, _)| (point, kind))
     }
 
     pub fn step(&self) -> Duration {
         self.timer.duration()
     }
 
-    pub fn done(&self) -> bool {
-        self.state.goals().all(|(_, ok)| ok)
+    pub fn outcome(&self) -> Outcome {
+        self.state.outcome()
+    }
+
+    pub fn moves(&self) -> usize {
+        self.state.moves()
+    }
+
+    pub fn par(&self) -> Option<usize> {
+        self.par
+    }
+
+    pub fn max_moves(&self) -> Option<usize> {
+        self.max_moves
     }
 
     pub fn width(&self) -> usize {
@@ -53,4 +163,43 @@ impl World {
     pub fn height(&self) -> usize {
         self.state.height()
     }
+
+    fn review(&mut self, diffs: &HashMap<usize, Diff>) {
+        if diffs.is_empty() {
+            return;
+        }
+
+        // a cheap check for each turn, and a search that replaces (and so
+        // cancels) the one of an older state.
+        let playing = self.state.outcome() == Outcome::Playing;
+        self.stuck = playing && self.state.hopeless();
+        self.search = (playing && !self.stuck).then(|| {
+            let state = self.state.present();
+            AsyncComputeTaskPool::get().spawn(async move { state.dead_end(Self::SEARCH_LIMIT) })
+        });
+    }
+
+    fn collect(diffs: impl Iterator<Item = Diff>) -> HashMap<usize, Diff> {
+        diffs.map(|diff| (diff.id, diff)).collect()
+    }
+
+    fn merge(diffs: &mut HashMap<usize, Diff>, more: impl Iterator<Item = Diff>) {
+        for diff in more {
+            match diffs.entry(diff.id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(diff);
+                }
+                Entry::Occupied(mut entry) => {
+                    let last = entry.get_mut();
+                    last.cube = diff.cube.or(last.cube);
+                    last.kind = diff.kind.or(last.kind);
+                    last.position = diff.position.or(last.position);
+                    last.movement = diff.movement.or(last.movement);
+                    last.constraint = diff.constraint.or(last.constraint);
+                    last.neighborhood = diff.neighborhood.or(last.neighborhood);
+                    last.warp |= diff.warp;
+                }
+            }
+        }
+    }
 }
@@ -13,6 +13,7 @@ pub fn setup(app: &mut App) {
         .add_systems(
             Update,
             (
+                system::hint,
                 system::position,
                 system::realpha,
                 system::recolor,
@@ -25,6 +26,12 @@ pub fn setup(app: &mut App) {
             Update,
             system::state.run_if(resource_exists::<model::World>),
         )
+        .add_systems(
+            Update,
+            (system::score, system::prompt, system::door)
+                .run_if(resource_exists_and_changed::<model::World>)
+                .after(system::state),
+        )
         .add_systems(
             PostUpdate,
             (
@@ -1,12 +1,18 @@
+mod floor;
+mod input;
 mod kind;
 mod motion;
 mod movement;
 mod neighborhood;
 mod point;
+mod ruleset;
 
-pub(crate) use motion::{Agreement, Motion};
+pub(crate) use motion::{Agreement, Motion, Progress};
 
+pub use floor::Floor;
+pub use input::Input;
 pub use kind::Kind;
 pub use movement::{Constraint, Movement};
 pub use neighborhood::{Adjacence, Neighborhood};
 pub use point::Point;
+pub use ruleset::RuleSet;
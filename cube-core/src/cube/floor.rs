@@ -0,0 +1,31 @@
+use super::{Movement, Point};
+
+/// Floor is a tile under cubes, which changes how they move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Floor {
+    /// a cube standing entirely on ice keeps sliding until blocked
+    Ice,
+    /// a cube standing on conveyors is pushed every turn
+    Conveyor(Movement),
+    /// a single-unit cube moving onto the portal appears at the exit if it
+    /// is free, while larger cubes are blocked by it
+    Portal(Point),
+    /// a cube may enter the gate only while moving in its direction
+    Gate(Movement),
+}
+
+impl Floor {
+    pub(crate) fn encode(&self, output: &mut Vec<u8>) {
+        output.push(match *self {
+            Floor::Ice => 0,
+            Floor::Conveyor(movement) => 1 + movement as u8,
+            Floor::Portal(_) => 5,
+            Floor::Gate(movement) => 6 + movement as u8,
+        });
+        if let Floor::Portal(exit) = *self {
+            output.extend_from_slice(&exit.x.to_le_bytes());
+            output.extend_from_slice(&exit.y.to_le_bytes());
+        }
+    }
+}
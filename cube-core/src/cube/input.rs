@@ -0,0 +1,41 @@
+use super::{Kind, Movement};
+
+/// Input is what controllers ask for in a turn, by kinds of cubes. Only kinds
+/// controlled by the rule set follow it. A single movement steers all of
+/// them at once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Input([Option<Movement>; Kind::ALL.len()]);
+
+impl Input {
+    /// An input where no one moves.
+    pub const fn new() -> Self {
+        Self([None; Kind::ALL.len()])
+    }
+
+    /// Let cubes of `kind` move to `movement`.
+    pub fn set(mut self, kind: Kind, movement: Option<Movement>) -> Self {
+        self.0[kind as usize] = movement;
+        self
+    }
+
+    pub fn get(&self, kind: Kind) -> Option<Movement> {
+        self.0[kind as usize]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
+
+impl From<Option<Movement>> for Input {
+    fn from(movement: Option<Movement>) -> Self {
+        Self([movement; Kind::ALL.len()])
+    }
+}
+
+impl From<Movement> for Input {
+    fn from(movement: Movement) -> Self {
+        Some(movement).into()
+    }
+}
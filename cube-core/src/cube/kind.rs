@@ -1,29 +1,25 @@
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     White,
     Green,
     Blue,
     Red,
+    Yellow,
+    Purple,
 }
 
 impl Kind {
-    pub const fn absorbable(self, other: Self) -> bool {
-        use Kind::*;
-        match self {
-            White => false,
-            Green => matches!(other, Blue),
-            Blue => matches!(other, Red),
-            Red => matches!(other, Green),
-        }
-    }
+    pub const ALL: [Kind; 6] = [
+        Kind::White,
+        Kind::Green,
+        Kind::Blue,
+        Kind::Red,
+        Kind::Yellow,
+        Kind::Purple,
+    ];
 
-    pub const fn linkable(self, other: Self) -> bool {
-        use Kind::*;
-        match self {
-            White => false,
-            Green => matches!(other, Green),
-            Blue => matches!(other, Blue),
-            Red => matches!(other, Red),
-        }
+    pub(crate) const fn bit(self) -> u32 {
+        1 << self as u32
     }
 }
@@ -44,6 +44,20 @@ impl Agreement {
 #[derive(Clone, Debug)]
 pub struct Motion(Any);
 
+/// Progress is a plain copy of a motion, which is used to save and restore.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Progress {
+    Stop,
+    Move {
+        looping: bool,
+        actions: Vec<(Option<Movement>, usize)>,
+        primary: usize,
+        secondary: usize,
+    },
+    Team(Vec<Progress>),
+}
+
 impl Motion {
     pub fn new() -> Self {
         Motion(Any::Stop)
@@ -77,6 +91,27 @@ impl Motion {
         Motion(self.take_inner())
     }
 
+    pub fn progress(&self) -> Progress {
+        self.0.progress()
+    }
+
+    pub fn from_progress(progress: &Progress) -> Option<Self> {
+        Any::from_progress(progress).map(Motion)
+    }
+
+    /// Write the remaining motion into bytes. Motions that produce the same
+    /// movements from now on are encoded identically.
+    pub fn encode(&self, output: &mut Vec<u8>) {
+        self.0.encode(output);
+    }
+
+    /// The next movement of a turn. Actions of zero times wait until the
+    /// player moves, which is told by `moved`, and then go on at once.
+    pub fn step(&mut self, moved: bool) -> Option<Option<Movement>> {
+        self.0 = self.take_inner().slim();
+        self.0.step(moved)
+    }
+
     fn take_inner(&mut self) -> Any {
         let mut that = Any::Stop;
         std::mem::swap(&mut that, &mut self.0);
@@ -88,8 +123,7 @@ impl Iterator for Motion {
     type Item = Option<Movement>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0 = self.take_inner().slim();
-        self.0.next()
+        self.step(false)
     }
 }
 
@@ -116,14 +150,95 @@ impl Any {
     }
 }
 
-impl Iterator for Any {
-    type Item = Option<Movement>;
+impl Any {
+    fn progress(&self) -> Progress {
+        match self {
+            Any::Stop => Progress::Stop,
+            Any::Move(x) => Progress::Move {
+                looping: x.source.looping,
+                actions: x.source.actions.to_vec(),
+                primary: x.primary,
+                secondary: x.secondary,
+            },
+            Any::Team(x) => Progress::Team(x.0.iter().map(Any::progress).collect()),
+        }
+    }
+
+    fn from_progress(progress: &Progress) -> Option<Self> {
+        Some(match progress {
+            Progress::Stop => Any::Stop,
+            Progress::Move {
+                looping,
+                actions,
+                primary,
+                secondary,
+            } => {
+                let valid = match actions.get(*primary) {
+                    None => *primary == actions.len() && *secondary == 0,
+                    Some(&(_, times)) => *secondary < times.max(1),
+                };
+                if !valid {
+                    return None;
+                }
+
+                Any::Move(Move {
+                    source: Arc::new(Sequence::new(*looping, actions.iter().cloned())),
+                    primary: *primary,
+                    secondary: *secondary,
+                })
+            }
+            Progress::Team(x) => Any::Team(Team(
+                x.iter()
+                    .map(Any::from_progress)
+                    .collect::<Option<Vec<_>>>()?,
+            )),
+        })
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        const STOP: u8 = 0;
+        const MOVE: u8 = 1;
+        const TEAM: u8 = 2;
 
-    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Any::Move(x) if !x.stopped() => {
+                output.push(MOVE);
+                x.encode(output);
+            }
+            Any::Team(x) => {
+                // members of a team are unordered and stopped ones do nothing.
+                let mut members =
+                    x.0.iter()
+                        .map(|one| {
+                            let mut bytes = Vec::new();
+                            one.encode(&mut bytes);
+                            bytes
+                        })
+                        .filter(|bytes| bytes[..] != [STOP])
+                        .collect::<Vec<_>>();
+                members.sort_unstable();
+
+                match members.len() {
+                    0 => output.push(STOP),
+                    1 => output.append(&mut members[0]),
+                    n => {
+                        output.push(TEAM);
+                        output.extend_from_slice(&(n as u32).to_le_bytes());
+                        members.iter().for_each(|bytes| output.extend(bytes));
+                    }
+                }
+            }
+            _ => output.push(STOP),
+        }
+    }
+}
+
+impl Any {
+    fn step(&mut self, moved: bool) -> Option<Option<Movement>> {
         match self {
             Any::Stop => None,
-            Any::Move(x) => x.next(),
-            Any::Team(x) => x.next(),
+            Any::Move(x) => x.step(moved),
+            Any::Team(x) => x.step(moved),
         }
     }
 }
@@ -135,10 +250,20 @@ struct Move {
     secondary: usize,
 }
 
-impl Iterator for Move {
-    type Item = Option<Movement>;
+impl Move {
+    fn stopped(&self) -> bool {
+        self.primary == self.source.actions.len()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.source.encode(output);
+        output.extend_from_slice(&(self.primary as u32).to_le_bytes());
+        output.extend_from_slice(&(self.secondary as u32).to_le_bytes());
+    }
+}
+
+impl Move {
+    fn step(&mut self, moved: bool) -> Option<Option<Movement>> {
         let actions = self.source.actions.as_ref();
 
         let limit = actions.len();
@@ -147,8 +272,11 @@ impl Iterator for Move {
         }
 
         let (movement, times) = actions[self.primary];
+        if times == 0 && !moved {
+            return Some(None);
+        }
         self.secondary += 1;
-        if self.secondary == times {
+        if self.secondary >= times {
             self.secondary = 0;
 
             self.primary += 1;
@@ -157,19 +285,20 @@ impl Iterator for Move {
             }
         }
 
-        return Some(movement);
+        match times {
+            0 => self.step(false),
+            _ => Some(movement),
+        }
     }
 }
 
 #[derive(Clone, Debug)]
 struct Team(Vec<Any>);
 
-impl Iterator for Team {
-    type Item = Option<Movement>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl Team {
+    fn step(&mut self, moved: bool) -> Option<Option<Movement>> {
         let mut vote = Agreement::new();
-        self.0.retain_mut(|one| match one.next() {
+        self.0.retain_mut(|one| match one.step(moved) {
             None => false,
             Some(choice) => {
                 vote.submit(choice);
@@ -200,6 +329,21 @@ impl Sequence {
             actions: actions.collect(),
         }
     }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.push(self.looping as u8);
+        output.extend_from_slice(&(self.actions.len() as u32).to_le_bytes());
+        for &(movement, times) in self.actions.iter() {
+            output.push(match movement {
+                None => 0,
+                Some(Movement::Left) => 1,
+                Some(Movement::Down) => 2,
+                Some(Movement::Up) => 3,
+                Some(Movement::Right) => 4,
+            });
+            output.extend_from_slice(&(times as u32).to_le_bytes());
+        }
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -231,6 +375,30 @@ mod tests {
         assert_eq!(list.next(), Some(Some(Movement::Left)));
     }
 
+    #[test]
+    fn waiting_motion() {
+        let list = [
+            (Some(Movement::Up), 1),
+            (None, 0),
+            (Some(Movement::Left), 1),
+        ];
+        let mut list = Motion::from_sequence(false, list.into_iter());
+        assert_eq!(list.step(false), Some(Some(Movement::Up)));
+        assert_eq!(list.step(false), Some(None));
+        assert_eq!(list.step(false), Some(None));
+
+        // waiting survives a save.
+        let mut list = Motion::from_progress(&list.progress()).unwrap();
+        assert_eq!(list.step(true), Some(Some(Movement::Left)));
+        assert_eq!(list.step(true), None);
+
+        let list = [(None, 0), (Some(Movement::Right), 1)];
+        let mut list = Motion::from_sequence(true, list.into_iter());
+        assert_eq!(list.step(true), Some(Some(Movement::Right)));
+        assert_eq!(list.step(false), Some(None));
+        assert_eq!(list.step(true), Some(Some(Movement::Right)));
+    }
+
     #[test]
     fn multiple_motion() {
         let team = [
@@ -254,4 +422,37 @@ mod tests {
         assert_eq!(team.next(), Some(Some(Movement::Up)));
         assert!(matches!(team, Motion(Any::Move(_))));
     }
+
+    #[test]
+    fn encoded_motion() {
+        let encode = |motion: &Motion| {
+            let mut bytes = Vec::new();
+            motion.encode(&mut bytes);
+            bytes
+        };
+        let one = || Motion::from_sequence(false, [(Some(Movement::Up), 1)].into_iter());
+        let two =
+            || Motion::from_sequence(true, [(None, 1), (Some(Movement::Left), 2)].into_iter());
+
+        // stopped motions
+        let mut done = one();
+        assert_ne!(encode(&done), encode(&Motion::new()));
+        assert_eq!(done.next(), Some(Some(Movement::Up)));
+        assert_eq!(encode(&done), encode(&Motion::new()));
+
+        // progress
+        let mut next = two();
+        assert_eq!(next.next(), Some(None));
+        assert_ne!(encode(&next), encode(&two()));
+        assert_eq!(next.next(), Some(Some(Movement::Left)));
+        assert_eq!(next.next(), Some(Some(Movement::Left)));
+        assert_eq!(encode(&next), encode(&two()));
+
+        // teams
+        let lhs = Motion::from_iter([one(), two()].into_iter());
+        let rhs = Motion::from_iter([two(), one()].into_iter());
+        assert_eq!(encode(&lhs), encode(&rhs));
+        let lhs = Motion::from_iter([done, two()].into_iter());
+        assert_eq!(encode(&lhs), encode(&two()));
+    }
 }
@@ -47,6 +47,11 @@ impl Into<Point> for &Adjacence {
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Neighborhood(u8);
 
 impl Neighborhood {
@@ -0,0 +1,155 @@
+use super::Kind;
+
+/// RuleSet decides how kinds of cubes interact with each other.
+///
+/// The default one is the rule of the game: white cubes are static, green
+/// cubes are controlled by the player, cubes of the same kind link together,
+/// and green absorbs blue, blue absorbs red, red absorbs green. For any two
+/// other colors, one of them absorbs the other:
+///
+/// | kind   | absorbs        |
+/// |--------|----------------|
+/// | green  | blue, yellow   |
+/// | blue   | red, purple    |
+/// | red    | green, purple  |
+/// | yellow | blue, red      |
+/// | purple | green, yellow  |
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleSet {
+    absorb: [u32; Kind::ALL.len()], // kinds absorbed by each kind
+    link: u32,                      // kinds that link with themselves
+    control: u32,                   // kinds moved by inputs
+    fixed: u32,                     // kinds that never move without commands
+}
+
+impl RuleSet {
+    /// A rule set where nothing interacts.
+    pub const fn new() -> Self {
+        Self {
+            absorb: [0; Kind::ALL.len()],
+            link: 0,
+            control: 0,
+            fixed: 0,
+        }
+    }
+
+    /// Let cubes of `kind` absorb cubes of `other`.
+    pub fn absorb(mut self, kind: Kind, other: Kind) -> Self {
+        self.absorb[kind as usize] |= other.bit();
+        self
+    }
+
+    /// Let cubes of `kind` merge when hitting each other. Cubes of different
+    /// kinds never merge, as a merged cube has only one kind.
+    pub fn link(mut self, kind: Kind) -> Self {
+        self.link |= kind.bit();
+        self
+    }
+
+    /// Let cubes of `kind` be moved by inputs.
+    pub fn control(mut self, kind: Kind) -> Self {
+        self.control |= kind.bit();
+        self
+    }
+
+    /// Make cubes of `kind` static. They become part of the background if
+    /// they have no commands, and never absorb others.
+    pub fn fix(mut self, kind: Kind) -> Self {
+        self.fixed |= kind.bit();
+        self
+    }
+
+    pub fn absorbable(&self, kind: Kind, other: Kind) -> bool {
+        self.absorb[kind as usize] & other.bit() != 0
+    }
+
+    pub fn linkable(&self, kind: Kind, other: Kind) -> bool {
+        kind == other && self.link & kind.bit() != 0
+    }
+
+    pub fn controlled(&self, kind: Kind) -> bool {
+        self.control & kind.bit() != 0
+    }
+
+    pub fn is_static(&self, kind: Kind) -> bool {
+        self.fixed & kind.bit() != 0
+    }
+
+    pub(crate) fn encode(&self, output: &mut Vec<u8>) {
+        let link = Kind::ALL.map(|kind| self.link & kind.bit());
+        let masks = self.absorb.iter().chain(link.iter());
+        let masks = masks.chain([&self.control, &self.fixed]);
+        masks.for_each(|mask| output.extend_from_slice(&mask.to_le_bytes()));
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        use Kind::*;
+        Self::new()
+            .fix(White)
+            .control(Green)
+            .link(Green)
+            .link(Blue)
+            .link(Red)
+            .link(Yellow)
+            .link(Purple)
+            .absorb(Green, Blue)
+            .absorb(Blue, Red)
+            .absorb(Red, Green)
+            .absorb(Green, Yellow)
+            .absorb(Blue, Purple)
+            .absorb(Red, Purple)
+            .absorb(Yellow, Blue)
+            .absorb(Yellow, Red)
+            .absorb(Purple, Green)
+            .absorb(Purple, Yellow)
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_rules() {
+        let rules = RuleSet::default();
+        let colors = Kind::ALL.into_iter().filter(|&kind| !rules.is_static(kind));
+        for kind in colors.clone() {
+            assert!(rules.linkable(kind, kind));
+            assert!(!rules.absorbable(kind, kind));
+
+            // every color takes exactly two others, and loses to the rest.
+            let preys = colors.clone().filter(|&o| rules.absorbable(kind, o));
+            assert_eq!(preys.count(), 2);
+            for other in colors.clone().filter(|&other| other != kind) {
+                assert!(!rules.linkable(kind, other));
+                assert!(rules.absorbable(kind, other) != rules.absorbable(other, kind));
+            }
+        }
+
+        assert!(rules.is_static(Kind::White));
+        assert!(Kind::ALL
+            .into_iter()
+            .all(|kind| !rules.absorbable(Kind::White, kind)));
+        assert!(Kind::ALL
+            .into_iter()
+            .all(|kind| !rules.linkable(Kind::White, kind)));
+        let controlled = Kind::ALL.into_iter().filter(|&kind| rules.controlled(kind));
+        assert_eq!(controlled.collect::<Vec<_>>(), [Kind::Green]);
+    }
+
+    #[test]
+    fn links() {
+        let rules = RuleSet::new().link(Kind::Green).link(Kind::Blue);
+        assert!(rules.linkable(Kind::Green, Kind::Green));
+        assert!(rules.linkable(Kind::Blue, Kind::Blue));
+        assert!(!rules.linkable(Kind::Green, Kind::Blue));
+        assert!(!rules.linkable(Kind::Blue, Kind::Green));
+        assert!(!rules.linkable(Kind::Red, Kind::Red));
+    }
+}
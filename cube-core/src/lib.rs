@@ -1,10 +1,13 @@
 mod rule;
 
 pub mod cube;
+pub mod objective;
+pub mod replay;
 pub mod seed;
+pub mod solver;
 pub mod state;
 
-pub use self::rule::{Diff, Unit};
+pub use self::rule::{Diff, Event, Reason, Unit};
 pub use self::state::*;
 
 #[cfg(test)]
@@ -23,10 +26,6 @@ mod tests {
 
         // STEP 00
         let seed = Seed {
-            info: Info {
-                title: "test".into(),
-                author: "test".into(),
-            },
             size: Size {
                 width: 3,
                 height: 3,
@@ -49,11 +48,13 @@ mod tests {
                 },
             ],
             destnations: vec![Point::new(1, 0), Point::new(0, 2)],
+            ..Default::default()
         };
-        let mut game = CubeCore::new(&seed);
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
         let stat = [
             Unit {
                 id: 0,
+                cube: 0,
                 kind: Kind::Green,
                 position: Point::new(0, 0),
                 movement: None,
@@ -62,6 +63,7 @@ mod tests {
             },
             Unit {
                 id: 1,
+                cube: 1,
                 kind: Kind::Blue,
                 position: Point::new(0, 1),
                 movement: None,
@@ -70,15 +72,18 @@ mod tests {
             },
             Unit {
                 id: 2,
+                cube: 2,
                 kind: Kind::White,
                 position: Point::new(1, 0),
                 movement: None,
                 constraint: Constraint::Free,
-                neighborhood: Neighborhood::new(),
+                neighborhood: Neighborhood::from(
+                    [Adjacence::LEFT_TOP, Adjacence::TOP, Adjacence::RIGHT_TOP].into_iter(),
+                ),
             },
         ];
         assert_eq!(game.iter().collect::<Vec<_>>(), stat);
-        assert_eq!(game.goals().filter(|(_, o)| *o).count(), 1);
+        assert_eq!(game.goals().filter(|(_, _, o)| *o).count(), 1);
 
         // STEP 01
         let diff = [
@@ -91,6 +96,7 @@ mod tests {
             },
             Diff {
                 id: 1,
+                cube: Some(0),
                 kind: Some(Kind::Green),
                 movement: Some(Some(Movement::Right)),
                 constraint: Some(Constraint::Stop),
@@ -99,7 +105,7 @@ mod tests {
             },
         ];
         assert_eq!(game.commit(Some(Movement::Right)).collect::<Vec<_>>(), diff);
-        assert_eq!(game.goals().filter(|(_, o)| *o).count(), 1);
+        assert_eq!(game.goals().filter(|(_, _, o)| *o).count(), 1);
 
         // STEP 02
         let diff = [
@@ -122,6 +128,7 @@ mod tests {
         let stat = [
             Unit {
                 id: 0,
+                cube: 0,
                 kind: Kind::Green,
                 position: Point::new(0, 1),
                 movement: Some(Movement::Down),
@@ -130,6 +137,7 @@ mod tests {
             },
             Unit {
                 id: 1,
+                cube: 0,
                 kind: Kind::Green,
                 position: Point::new(0, 2),
                 movement: Some(Movement::Down),
@@ -138,14 +146,17 @@ mod tests {
             },
             Unit {
                 id: 2,
+                cube: 2,
                 kind: Kind::White,
                 position: Point::new(1, 0),
                 movement: None,
                 constraint: Constraint::Free,
-                neighborhood: Neighborhood::new(),
+                neighborhood: Neighborhood::from(
+                    [Adjacence::LEFT_TOP, Adjacence::TOP, Adjacence::RIGHT_TOP].into_iter(),
+                ),
             },
         ];
         assert_eq!(game.iter().collect::<Vec<_>>(), stat);
-        assert_eq!(game.goals().filter(|(_, o)| *o).count(), 2);
+        assert_eq!(game.goals().filter(|(_, _, o)| *o).count(), 2);
     }
 }
@@ -0,0 +1,311 @@
+use super::{
+    cube::{Input, Kind, Movement, Point},
+    rule::Collection,
+    seed::Seed,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// export
+
+/// Objective is a condition to complete or to fail a level. A level without
+/// any of `Cover`, `Eliminate` and `Survive` is completed by covering all
+/// destinations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Objective {
+    /// cover all destinations
+    Cover,
+    /// leave no movable cube of the kind
+    Eliminate(Kind),
+    /// reach the number of turns
+    Survive(usize),
+    /// fail if the cube of the index in `Seed::cubes` is absorbed
+    Protect(usize),
+    /// fail after more moves than the number
+    Within(usize),
+}
+
+impl Objective {
+    pub(crate) fn encode(&self, output: &mut Vec<u8>) {
+        let (tag, value) = match *self {
+            Objective::Cover => (0, 0),
+            Objective::Eliminate(kind) => (1, kind as u64),
+            Objective::Survive(turns) => (2, turns as u64),
+            Objective::Protect(ident) => (3, ident as u64),
+            Objective::Within(moves) => (4, moves as u64),
+        };
+        output.push(tag);
+        output.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    Playing,
+    Complete,
+    Failed,
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// internal
+
+/// Judge decides the outcome of a collection, and knows who plays it.
+#[derive(Clone, Debug)]
+pub(crate) struct Judge {
+    pub dest: Vec<(Point, Option<Kind>)>,
+    pub objectives: Vec<Objective>,
+    pub players: Vec<Kind>,
+}
+
+impl Judge {
+    pub fn outcome(&self, collection: &Collection) -> Outcome {
+        use Objective::*;
+
+        // a level fails at once, even if other objectives are done.
+        let failed = self.objectives.iter().any(|objective| match *objective {
+            Protect(ident) => collection.lost(ident),
+            Within(limit) => collection.moves() > limit,
+            _ => false,
+        });
+        if failed {
+            return Outcome::Failed;
+        }
+
+        let mut goals = self
+            .objectives
+            .iter()
+            .filter(|objective| matches!(objective, Cover | Eliminate(_) | Survive(_)))
+            .peekable();
+        let done = match goals.peek() {
+            None => self.covered(collection),
+            Some(_) => goals.all(|objective| match *objective {
+                Eliminate(kind) => collection.count(kind) == 0,
+                Survive(turns) => collection.turn() >= turns,
+                _ => self.covered(collection),
+            }),
+        };
+        match done {
+            true => Outcome::Complete,
+            false => Outcome::Playing,
+        }
+    }
+
+    /// Inputs that lead to different turns: a movement for each player if
+    /// there are more than one, or else a movement for all.
+    pub fn inputs(&self) -> Vec<Input> {
+        const MOVEMENTS: [Option<Movement>; 5] = [
+            None,
+            Some(Movement::Left),
+            Some(Movement::Down),
+            Some(Movement::Up),
+            Some(Movement::Right),
+        ];
+
+        match self.players.as_slice() {
+            [] | [_] => MOVEMENTS.map(Input::from).to_vec(),
+            players => players.iter().fold(vec![Input::new()], |inputs, &kind| {
+                (inputs.into_iter())
+                    .flat_map(|input| MOVEMENTS.map(|movement| input.set(kind, movement)))
+                    .collect()
+            }),
+        }
+    }
+
+    /// Whether the level can never be complete, by counting units that may
+    /// cover destinations. It is cheap but far from complete.
+    pub fn hopeless(&self, collection: &Collection) -> bool {
+        if self.outcome(collection) == Outcome::Failed {
+            return true;
+        }
+        let covering = self
+            .objectives
+            .iter()
+            .all(|objective| !matches!(objective, Objective::Eliminate(_) | Objective::Survive(_)));
+        if !covering {
+            return false;
+        }
+
+        // walls never cover destinations.
+        if self.dest.iter().any(|&(o, _)| collection.walled(o)) {
+            return true;
+        }
+
+        // units are never created or destroyed, and they change kinds only
+        // by absorption.
+        let open = self
+            .dest
+            .iter()
+            .filter(|(o, _)| collection.fixed(*o).is_none());
+        let total = Kind::ALL.iter().map(|&kind| collection.units(kind)).sum();
+        if open.count() > total {
+            return true;
+        }
+
+        let rule = collection.rule();
+        Kind::ALL.into_iter().any(|kind| {
+            let (mut fixed, mut open) = (false, 0);
+            for &(o, _) in self.dest.iter().filter(|(_, k)| *k == Some(kind)) {
+                match collection.fixed(o) {
+                    Some(other) => fixed |= other != kind,
+                    None => open += 1,
+                }
+            }
+
+            // kinds that may become this kind by absorption.
+            let mut reach = kind.bit();
+            loop {
+                let next = (Kind::ALL.into_iter())
+                    .filter(|k| reach & k.bit() != 0)
+                    .flat_map(|k| {
+                        Kind::ALL
+                            .into_iter()
+                            .filter(move |&o| rule.absorbable(k, o))
+                    })
+                    .fold(reach, |bits, o| bits | o.bit());
+                if next == reach {
+                    break;
+                }
+                reach = next;
+            }
+
+            let have = (Kind::ALL.into_iter())
+                .filter(|k| reach & k.bit() != 0)
+                .map(|k| collection.units(k))
+                .sum::<usize>();
+            fixed || open > have
+        })
+    }
+
+    /// Counters of the collection that matter to objectives. They are capped
+    /// so that a search over states stays finite.
+    pub fn stage(&self, collection: &Collection) -> (usize, usize) {
+        let mut stage = (0, 0);
+        for objective in self.objectives.iter() {
+            match *objective {
+                Objective::Survive(turns) => stage.0 = stage.0.max(collection.turn().min(turns)),
+                Objective::Within(_) => stage.1 = collection.moves(),
+                _ => {}
+            }
+        }
+        stage
+    }
+
+    fn covered(&self, collection: &Collection) -> bool {
+        self.dest
+            .iter()
+            .all(|&(point, kind)| collection.covers(point, kind))
+    }
+}
+
+impl From<&Seed> for Judge {
+    fn from(seed: &Seed) -> Self {
+        let limit = seed.info.max_moves.map(Objective::Within);
+        Self {
+            dest: seed.goals().collect(),
+            objectives: seed.objectives.iter().cloned().chain(limit).collect(),
+            players: seed.players.clone(),
+        }
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cube::{Movement, RuleSet},
+        seed::Cube,
+        solver, CubeCore,
+    };
+
+    #[test]
+    fn demands() {
+        /*****
+         *GrR*
+         *g W*
+         *****/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Red, Point::new(2, 0)),
+                Cube::unit(Kind::White, Point::new(2, 1)),
+            ],
+            destnations: vec![Point::new(2, 1)],
+            demands: vec![
+                (Point::new(1, 0), Kind::Red),
+                (Point::new(0, 1), Kind::Green),
+            ],
+            ..Seed::sized(3, 2)
+        };
+
+        // a plain destination accepts anything, including white walls.
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let covered = |game: &CubeCore| game.goals().map(|(_, _, o)| o).collect::<Vec<_>>();
+        assert_eq!(covered(&game), vec![true, false, false]);
+
+        // a green cube doesn't satisfy a red destination until it is absorbed.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(covered(&game), vec![true, false, false]);
+        game.commit(None).for_each(drop);
+        assert_eq!(covered(&game), vec![true, true, false]);
+
+        // no green cube is left to cover the green one.
+        let solution = solver::solve(&seed, &RuleSet::default(), 1000);
+        assert_eq!(solution, solver::Solution::Unsolvable);
+    }
+
+    #[test]
+    fn objectives() {
+        /*****
+         *G R*
+         *****/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Red, Point::new(2, 0)),
+            ],
+            objectives: vec![Objective::Survive(2), Objective::Protect(0)],
+            ..Seed::sized(3, 1)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        assert_eq!(game.outcome(), Outcome::Playing);
+        game.commit(None).for_each(drop);
+        assert_eq!((game.turn(), game.moves()), (1, 0));
+        assert_eq!(game.outcome(), Outcome::Playing);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!((game.turn(), game.moves()), (2, 1));
+        assert_eq!(game.outcome(), Outcome::Complete);
+
+        // the protected green cube is absorbed by the red one.
+        game.commit(None).for_each(drop);
+        assert_eq!(game.outcome(), Outcome::Failed);
+        let restored = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!((restored.turn(), restored.moves()), (3, 1));
+        assert_eq!(restored.outcome(), Outcome::Failed);
+
+        // undo restores counters, too.
+        game.undo().for_each(drop);
+        assert_eq!(game.turn(), 2);
+        assert_eq!(game.outcome(), Outcome::Complete);
+
+        // only inputs count as moves, and it fails with too many of them.
+        let mut seed = seed;
+        seed.info.max_moves = Some(1);
+        seed.destnations = vec![Point::new(1, 0)];
+        seed.objectives = vec![];
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        game.commit(Some(Movement::Left)).for_each(drop);
+        game.commit(None).for_each(drop);
+        assert_eq!((game.turn(), game.moves()), (2, 1));
+        assert_eq!(game.outcome(), Outcome::Playing);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(game.goals().filter(|(_, _, o)| *o).count(), 1);
+        assert_eq!(game.outcome(), Outcome::Failed);
+    }
+}
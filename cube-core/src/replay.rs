@@ -0,0 +1,207 @@
+use super::{
+    cube::{Input, Kind, Point, RuleSet},
+    objective::{Judge, Outcome},
+    rule::{Diff, Image},
+    seed::Seed,
+    state::CubeCore,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// export
+
+/// Replay is a record of inputs to `CubeCore::commit`, which is bound to a
+/// level by the fingerprint of its seed and rules.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    pub seed: u64,
+    pub inputs: Vec<Input>,
+}
+
+/// The result of running a replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Playback {
+    pub turns: Vec<Vec<Diff>>,                   // diffs of each input
+    pub goals: Vec<(Point, Option<Kind>, bool)>, // goals at the end
+    pub done: bool,                              // whether the level is complete
+    pub outcome: Outcome,                        // the outcome by objectives
+    pub fingerprint: u64,                        // the final state
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    MismatchedSeed { expected: u64, found: u64 },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MismatchedSeed { expected, found } => write!(
+                f,
+                "replay is recorded for seed {:016x} but got {:016x}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn new(seed: &Seed, rules: &RuleSet) -> Self {
+        Self {
+            seed: Self::fingerprint(seed, rules),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// A stable hash of the starting state, destinations, objectives and rules
+    /// of a level.
+    /// The title, the author and the order of cubes are ignored.
+    pub fn fingerprint(seed: &Seed, rules: &RuleSet) -> u64 {
+        let mut points = seed.destnations.clone();
+        points.sort_unstable_by_key(|o| (o.y, o.x));
+
+        let mut demands = seed.demands.clone();
+        demands.sort_unstable_by_key(|(o, _)| (o.y, o.x));
+
+        let mut bytes = Vec::with_capacity(8 * points.len() + 13 * demands.len() + 4);
+        Image::put_points(&mut bytes, points.into_iter());
+        for (o, kind) in demands {
+            bytes.push(kind as u8);
+            Image::put_points(&mut bytes, std::iter::once(o));
+        }
+        let mut objectives = (Judge::from(seed).objectives.iter())
+            .map(|objective| {
+                let mut bytes = Vec::with_capacity(9);
+                objective.encode(&mut bytes);
+                bytes
+            })
+            .collect::<Vec<_>>();
+        objectives.sort_unstable();
+        bytes.extend(objectives.concat());
+        Image::digest(CubeCore::new(seed, rules).fingerprint(), &bytes)
+    }
+
+    pub fn push(&mut self, input: impl Into<Input>) {
+        self.inputs.push(input.into());
+    }
+
+    /// Feed all inputs to a new game of the seed.
+    pub fn run(&self, seed: &Seed, rules: &RuleSet) -> Result<Playback, ReplayError> {
+        let found = Self::fingerprint(seed, rules);
+        if found != self.seed {
+            return Err(ReplayError::MismatchedSeed {
+                expected: self.seed,
+                found,
+            });
+        }
+
+        let mut game = CubeCore::new(seed, rules);
+        let turns = self
+            .inputs
+            .iter()
+            .map(|&input| game.commit(input).collect())
+            .collect();
+        let outcome = game.outcome();
+        Ok(Playback {
+            turns,
+            goals: game.goals().collect(),
+            done: outcome == Outcome::Complete,
+            outcome,
+            fingerprint: game.fingerprint(),
+        })
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cube::Kind,
+        seed::Cube,
+        solver::{solve, Solution},
+    };
+
+    fn make(destnations: Vec<Point>) -> Seed {
+        /*****
+         *G B*
+         *   *
+         *  x*
+         *****/
+        Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Blue, Point::new(2, 0)),
+            ],
+            destnations,
+            ..Seed::sized(3, 3)
+        }
+    }
+
+    #[test]
+    fn replay() {
+        let seed = make(vec![Point::new(2, 2)]);
+        let rules = RuleSet::default();
+        let mut replay = Replay::new(&seed, &rules);
+        let Solution::Found(inputs) = solve(&seed, &rules, 1000) else {
+            panic!("unsolvable");
+        };
+        inputs.iter().for_each(|&input| replay.push(input));
+
+        let playback = replay.run(&seed, &rules).unwrap();
+        assert!(playback.done);
+        assert_eq!(playback.turns.len(), inputs.len());
+        assert_eq!(playback.goals, vec![(Point::new(2, 2), None, true)]);
+        assert_eq!(replay.run(&seed, &rules).unwrap(), playback);
+
+        let mut game = CubeCore::new(&seed, &rules);
+        for (&input, diffs) in inputs.iter().zip(playback.turns.iter()) {
+            assert_eq!(&game.commit(input).collect::<Vec<_>>(), diffs);
+        }
+        assert_eq!(game.fingerprint(), playback.fingerprint);
+
+        replay.inputs.pop();
+        assert!(!replay.run(&seed, &rules).unwrap().done);
+    }
+
+    #[test]
+    fn mismatched() {
+        let seed = make(vec![Point::new(2, 2)]);
+        let rules = RuleSet::default();
+        let replay = Replay::new(&seed, &rules);
+
+        let mut same = make(vec![Point::new(2, 2)]);
+        same.info.title = "other".into();
+        same.cubes.reverse();
+        assert_eq!(Replay::fingerprint(&same, &rules), replay.seed);
+
+        let other = make(vec![Point::new(1, 2)]);
+        assert_eq!(
+            replay.run(&other, &rules),
+            Err(ReplayError::MismatchedSeed {
+                expected: replay.seed,
+                found: Replay::fingerprint(&other, &rules),
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        let seed = make(vec![Point::new(2, 2)]);
+        let rules = RuleSet::default();
+        let mut replay = Replay::new(&seed, &rules);
+        replay.push(Some(crate::cube::Movement::Right));
+        replay.push(None);
+
+        let text = serde_json::to_string(&replay).unwrap();
+        let other = serde_json::from_str::<Replay>(&text).unwrap();
+        assert_eq!(other, replay);
+        assert_eq!(other.run(&seed, &rules), replay.run(&seed, &rules));
+    }
+}
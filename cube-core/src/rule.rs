@@ -1,14 +1,17 @@
+mod board;
 mod collection;
 mod extension;
 mod frozen;
 mod lookup;
 mod output;
 mod snapshot;
+mod terrain;
 
+pub(crate) use board::*;
 pub(crate) use collection::*;
-pub(crate) use extension::*;
 pub(crate) use frozen::*;
 pub(crate) use lookup::*;
 pub(crate) use snapshot::*;
+pub(crate) use terrain::*;
 
-pub use output::{Diff, Unit};
+pub use output::{Diff, Event, Reason, Unit};
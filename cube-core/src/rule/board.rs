@@ -0,0 +1,72 @@
+use super::{extension::CollisionExtension, lookup::Collision};
+use crate::cube::{Neighborhood, Point};
+
+/// Board is the size of a collection. On a wrapping board, points out of
+/// range come back from the opposite edges.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Board {
+    width: i32,
+    height: i32,
+    wrap: bool,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize, wrap: bool) -> Self {
+        Self {
+            width: width as i32,
+            height: height as i32,
+            wrap,
+        }
+    }
+
+    pub fn wrap(&self, point: Point) -> Point {
+        match self.wrap && self.width > 0 && self.height > 0 {
+            true => Point::new(
+                point.x.rem_euclid(self.width),
+                point.y.rem_euclid(self.height),
+            ),
+            false => point,
+        }
+    }
+
+    pub fn wrapping(&self) -> bool {
+        self.wrap
+    }
+
+    /// The same board in doubled coordinates.
+    pub fn doubled(&self) -> Self {
+        Self {
+            width: self.width * 2,
+            height: self.height * 2,
+            wrap: self.wrap,
+        }
+    }
+
+    pub fn neighborhood(&self, collision: &impl Collision, point: Point) -> Neighborhood {
+        match self.wrap {
+            true => Neighborhood::from(
+                Neighborhood::AROUNDS
+                    .into_iter()
+                    .filter(|o| collision.existed(self.wrap(point + o.into()))),
+            ),
+            false => collision.neighborhood(point),
+        }
+    }
+
+    /// Like `neighborhood`, but edges of a board that does not wrap count as
+    /// neighbors.
+    pub fn neighborhood_or_border(&self, collision: &impl Collision, point: Point) -> Neighborhood {
+        match self.wrap {
+            true => self.neighborhood(collision, point),
+            false => collision.neighborhood_or_border(point),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.height as usize
+    }
+}
@@ -1,32 +1,62 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     sync::Arc,
 };
 
-use super::{output, CollisionExtension, Digraph, DisjointSet, Frozen, HashSetCollision, Snapshot};
-use crate::cube::{Adjacence, Agreement, Constraint, Kind, Motion, Movement, Neighborhood, Point};
+use super::{
+    output::{self, Event, Reason},
+    Board, Digraph, DisjointSet, Frozen, HashSetCollision, Snapshot, Terrain,
+};
+use crate::cube::{
+    Adjacence, Agreement, Constraint, Floor, Input, Kind, Motion, Movement, Neighborhood, Point,
+    Progress, RuleSet,
+};
 
 /////////////////////////////////////////////////////////////////////////////
 // export
 
 #[derive(Clone, Debug)]
 pub struct Collection {
-    cube: Vec<Cube>,   // cubes (sets of units)
-    area: Arc<Frozen>, // background and obstacles
+    cube: Vec<Cube>,           // cubes (sets of units)
+    area: Arc<Frozen>,         // background and obstacles
+    land: Terrain,             // doors that open and close
+    rule: Arc<RuleSet>,        // interactions between kinds
+    news: Vec<Event>,          // events of the last turn
+    warp: Vec<(usize, Point)>, // units teleported in the last turn, and their sources
+    fall: Option<Movement>,    // direction that unsupported cubes fall
+    fate: Vec<Option<usize>>,  // the cube that absorbed each cube
+    lost: Vec<bool>,           // whether each cube is absorbed by another kind
+    turn: usize,               // number of commits
+    moves: usize,              // number of commits with inputs
 }
 
 impl Collection {
-    pub fn new<'a, I>(width: usize, height: usize, it: I) -> Self
+    pub fn new<'a, I, W, F, G>(
+        board: Board,
+        rule: &RuleSet,
+        it: I,
+        walls: W,
+        floors: F,
+        wires: G,
+        gravity: Option<Movement>,
+    ) -> Self
     where
         I: Iterator<Item = (Kind, &'a [Point], Motion)> + 'a,
+        W: Iterator<Item = Point>,
+        F: Iterator<Item = (Point, Floor)>,
+        G: Iterator<Item = (&'a [Point], &'a [Point])>,
     {
         let mut index = 0;
         let mut count = 0;
         let mut cubes = Vec::new();
         let mut other = Vec::new();
-        for (kind, points, motion) in it {
-            if kind == Kind::White && motion.is_stopped() {
-                other.push(points);
+        let mut fate = Vec::new();
+        let mut lost = Vec::new();
+        for (ident, (kind, points, motion)) in it.enumerate() {
+            fate.push(None);
+            lost.push(false);
+            if rule.is_static(kind) && motion.is_stopped() {
+                other.push((ident, kind, points));
                 continue;
             }
 
@@ -37,14 +67,15 @@ impl Collection {
                 .map(|(offset, &point)| Unit {
                     index: count + offset,
                     position: point,
-                    neighborhood: collision.neighborhood(point),
+                    neighborhood: board.neighborhood(&collision, point),
                 })
                 .collect::<Vec<_>>();
-            let contours = Arc::new(Contours::new(&units));
+            let contours = Arc::new(Contours::new(&units, board));
 
             // note: make sure loop invariant work for our cubes' status.
             let cube = Cube {
                 index,
+                ident,
                 kind,
                 units,
                 motion,
@@ -52,6 +83,7 @@ impl Collection {
                 balanced: false,
                 movement: None,
                 constraint: Constraint::Free,
+                slide: None,
             };
 
             cubes.push(cube);
@@ -59,9 +91,145 @@ impl Collection {
             index += 1;
         }
 
-        Self {
+        let mut it = Self {
+            cube: cubes,
+            area: Arc::new(Frozen::new(board, other.into_iter(), walls, floors)),
+            land: Terrain::new(board.width(), board.height(), wires),
+            rule: Arc::new(rule.clone()),
+            news: Vec::new(),
+            warp: Vec::new(),
+            fall: gravity,
+            fate,
+            lost,
+            turn: 0,
+            moves: 0,
+        };
+        it.update_terrain();
+        it
+    }
+
+    pub fn from_archive(archive: &Archive) -> Option<Self> {
+        let board = Board::new(archive.width, archive.height, archive.wrap);
+        let mut count = 0;
+        let mut cubes = Vec::with_capacity(archive.cubes.len());
+        for (index, cube) in archive.cubes.iter().enumerate() {
+            let collision = HashSetCollision::new(cube.units.iter().map(|(_, o)| o));
+            let units = cube
+                .units
+                .iter()
+                .map(|&(index, point)| Unit {
+                    index,
+                    position: point,
+                    neighborhood: board.neighborhood(&collision, point),
+                })
+                .collect::<Vec<_>>();
+            if units.is_empty() {
+                return None;
+            }
+
+            count += units.len();
+            cubes.push(Cube {
+                index,
+                ident: cube.ident,
+                kind: cube.kind,
+                contours: Arc::new(Contours::new(&units, board)),
+                units,
+                motion: Motion::from_progress(&cube.motion)?,
+                balanced: false,
+                movement: cube.movement,
+                constraint: cube.constraint,
+                slide: cube.slide,
+            });
+        }
+
+        // unit indexes are used by snapshots, so they must be a permutation.
+        let mut visit = vec![false; count];
+        for unit in cubes.iter().flat_map(|cube| cube.units.iter()) {
+            match visit.get_mut(unit.index) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return None,
+            }
+        }
+
+        // so do identities of cubes.
+        let fate = archive.fate.clone();
+        let mut visit = vec![false; fate.len()];
+        for ident in cubes.iter().map(|cube| cube.ident) {
+            match visit.get_mut(ident) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return None,
+            }
+        }
+        if fate.iter().flatten().any(|&ident| ident >= fate.len()) {
+            return None;
+        }
+        if archive.lost.len() != fate.len() {
+            return None;
+        }
+
+        let frozen = Frozen::from_units(
+            board,
+            archive.frozen.iter().cloned(),
+            archive.walls.iter().cloned(),
+            archive.floors.iter().cloned(),
+        );
+        let wires = (archive.wires.iter()).map(|(plates, doors)| (&plates[..], &doors[..]));
+        let mut it = Self {
             cube: cubes,
-            area: Arc::new(Frozen::new(width, height, other.into_iter())),
+            area: Arc::new(frozen),
+            land: Terrain::new(board.width(), board.height(), wires),
+            rule: Arc::new(archive.rule.clone()),
+            news: Vec::new(),
+            warp: Vec::new(),
+            fall: archive.gravity,
+            fate,
+            lost: archive.lost.clone(),
+            turn: archive.turn,
+            moves: archive.moves,
+        };
+        it.update_terrain();
+        Some(it)
+    }
+
+    pub fn archive(&self) -> Archive {
+        Archive {
+            width: self.width(),
+            height: self.height(),
+            wrap: self.area.board().wrapping(),
+            cubes: self
+                .cube
+                .iter()
+                .map(|cube| ArchivedCube {
+                    ident: cube.ident,
+                    kind: cube.kind,
+                    units: cube.units.iter().map(|u| (u.index, u.position)).collect(),
+                    motion: cube.motion.progress(),
+                    movement: cube.movement,
+                    constraint: cube.constraint,
+                    slide: cube.slide,
+                })
+                .collect(),
+            frozen: (self.area.iter().enumerate())
+                .map(|(i, &(o, n))| {
+                    (
+                        self.area.identity(i),
+                        self.area.kind(o).unwrap_or(Kind::White),
+                        o,
+                        n,
+                    )
+                })
+                .collect(),
+            walls: self.area.walls().map(|&(o, _)| o).collect(),
+            floors: self.area.floors().cloned().collect(),
+            wires: (self.land.wires())
+                .map(|(plates, doors)| (plates.to_vec(), doors.to_vec()))
+                .collect(),
+            gravity: self.fall,
+            rule: self.rule.as_ref().clone(),
+            fate: self.fate.clone(),
+            lost: self.lost.clone(),
+            turn: self.turn,
+            moves: self.moves,
         }
     }
 
@@ -76,6 +244,7 @@ impl Collection {
     pub fn snapshot(&self) -> Snapshot {
         let default = output::Unit {
             id: 0,
+            cube: 0,
             kind: Kind::White,
             position: Point::new(0, 0),
             movement: None,
@@ -88,6 +257,7 @@ impl Collection {
             for unit in cube.units.iter() {
                 output[unit.index] = output::Unit {
                     id: unit.index,
+                    cube: cube.ident,
                     kind: cube.kind,
                     position: unit.position,
                     movement: cube.movement,
@@ -96,13 +266,201 @@ impl Collection {
                 };
             }
         }
-        Snapshot::new(output, Arc::clone(&self.area))
+        Snapshot::new(output, Arc::clone(&self.area), self.warp.clone())
+    }
+
+    /// Whether a destination at `position` is covered by a cube of the
+    /// demanded kind, or by any cube if there is no demand.
+    pub fn covers(&self, position: Point, demand: Option<Kind>) -> bool {
+        let found = self
+            .cube
+            .iter()
+            .find(|cube| cube.units.iter().any(|unit| unit.position == position))
+            .map(|cube| cube.kind)
+            .or_else(|| self.area.kind(position));
+        found.is_some_and(|kind| demand.is_none_or(|o| o == kind))
+    }
+
+    pub fn image(&self) -> Image {
+        // cubes never overlap, so sorting their bytes makes a canonical order.
+        let mut cubes = self
+            .cube
+            .iter()
+            .filter(|cube| cube.alive())
+            .map(|cube| {
+                let mut points = cube.units.iter().map(|u| u.position).collect::<Vec<_>>();
+                points.sort_unstable_by_key(|o| (o.y, o.x));
+
+                let mut bytes = Vec::with_capacity(8 * points.len() + 16);
+                bytes.push(cube.kind as u8);
+                Image::put_points(&mut bytes, points.into_iter());
+                cube.motion.encode(&mut bytes);
+                bytes.push(match cube.slide {
+                    None => 0,
+                    Some(movement) => 1 + movement as u8,
+                });
+                bytes
+            })
+            .collect::<Vec<_>>();
+        cubes.sort_unstable();
+
+        Image(cubes.concat().into())
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        let hash = Image::digest(Image::OFFSET, &self.scenery());
+        Image::digest(hash, &self.image().0)
+    }
+
+    fn scenery(&self) -> Vec<u8> {
+        let mut points = self.area.iter().map(|(o, _)| *o).collect::<Vec<_>>();
+        points.sort_unstable_by_key(|o| (o.y, o.x));
+
+        let mut bytes = Vec::with_capacity(8 * points.len() + 12);
+        bytes.extend_from_slice(&(self.width() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.height() as u32).to_le_bytes());
+        Image::put_points(&mut bytes, points.into_iter());
+        self.rule.encode(&mut bytes);
+
+        // levels without walls keep the same fingerprint as before.
+        let mut walls = self.area.walls().map(|(o, _)| *o).collect::<Vec<_>>();
+        if !walls.is_empty() {
+            walls.sort_unstable_by_key(|o| (o.y, o.x));
+            bytes.extend_from_slice(&(walls.len() as u32).to_le_bytes());
+            Image::put_points(&mut bytes, walls.into_iter());
+        }
+        let mut floors = self.area.floors().cloned().collect::<Vec<_>>();
+        if !floors.is_empty() {
+            floors.sort_unstable_by_key(|(o, _)| (o.y, o.x));
+            bytes.extend_from_slice(&(floors.len() as u32).to_le_bytes());
+            for (o, floor) in floors {
+                floor.encode(&mut bytes);
+                Image::put_points(&mut bytes, std::iter::once(o));
+            }
+        }
+        if !self.land.is_empty() {
+            bytes.extend_from_slice(&(self.land.wires().count() as u32).to_le_bytes());
+            for (plates, doors) in self.land.wires() {
+                Image::put_points(&mut bytes, plates.iter().copied());
+                Image::put_points(&mut bytes, doors.iter().copied());
+            }
+        }
+        if self.area.board().wrapping() {
+            bytes.push(1);
+        }
+        if let Some(movement) = self.fall {
+            bytes.push(2 + movement as u8);
+        }
+
+        // nor do levels where only white cubes are static.
+        let mut colors = (self.area.iter())
+            .filter_map(|&(o, _)| self.area.kind(o).map(|kind| (o, kind)))
+            .filter(|&(_, kind)| kind != Kind::White)
+            .collect::<Vec<_>>();
+        if !colors.is_empty() {
+            colors.sort_unstable_by_key(|(o, _)| (o.y, o.x));
+            bytes.extend_from_slice(&(colors.len() as u32).to_le_bytes());
+            for (o, kind) in colors {
+                bytes.push(kind as u8);
+                Image::put_points(&mut bytes, std::iter::once(o));
+            }
+        }
+        bytes
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.news
+    }
+
+    pub fn absorbed_into(&self, ident: usize) -> Option<usize> {
+        self.fate.get(ident).copied().flatten()
+    }
+
+    /// Whether the cube, or any cube it is merged into, has been absorbed
+    /// by another kind.
+    pub fn lost(&self, ident: usize) -> bool {
+        let mut cursor = Some(ident);
+        while let Some(ident) = cursor {
+            match self.lost.get(ident) {
+                None => return false,
+                Some(true) => return true,
+                Some(false) => cursor = self.absorbed_into(ident),
+            }
+        }
+        false
+    }
+
+    /// The number of living cubes of the kind, except static ones.
+    pub fn count(&self, kind: Kind) -> usize {
+        self.cube
+            .iter()
+            .filter(|cube| cube.alive() && cube.kind == kind)
+            .count()
+    }
+
+    /// The number of units of living cubes of the kind, except static ones.
+    pub fn units(&self, kind: Kind) -> usize {
+        self.cube
+            .iter()
+            .filter(|cube| cube.kind == kind)
+            .map(|cube| cube.units.len())
+            .sum()
+    }
+
+    /// The kind of the static cube that occupies the point forever.
+    pub fn fixed(&self, position: Point) -> Option<Kind> {
+        self.area.kind(position)
+    }
+
+    /// Whether the point is a wall, which never covers a destination.
+    pub fn walled(&self, position: Point) -> bool {
+        self.area.walled(position)
     }
 
-    pub fn commit(&mut self, movement: Option<Movement>) {
+    pub fn walls(&self) -> impl Iterator<Item = (Point, Neighborhood)> + '_ {
+        self.area.walls().cloned()
+    }
+
+    pub fn floors(&self) -> impl Iterator<Item = (Point, Floor)> + '_ {
+        self.area.floors().cloned()
+    }
+
+    pub fn plates(&self) -> impl Iterator<Item = Point> + '_ {
+        self.land.plates()
+    }
+
+    pub fn doors(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
+        self.land.doors()
+    }
+
+    pub fn rule(&self) -> &RuleSet {
+        &self.rule
+    }
+
+    /// The number of commits so far.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// The number of commits with inputs so far.
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Play one turn. Cubes move by inputs, commands and floors first, and
+    /// then cubes that nothing holds fall along gravity as a step of their
+    /// own. So gravity never votes with other movements, which would keep a
+    /// cube moving sideways in the air, and a cube may move and fall in the
+    /// same turn.
+    pub fn commit(&mut self, input: Input) {
+        self.turn += 1;
+        self.moves += !input.is_empty() as usize;
+
         // clean and update movements.
-        self.update_cube_status();
-        self.update_cube_movement(movement);
+        self.update_cube_status(!input.is_empty());
+        self.update_cube_movement(input);
+        self.update_cube_conveying();
+        self.update_cube_sliding();
 
         // try to connect cubes directly.
         self.process_imbalanced_cubes();
@@ -120,44 +478,196 @@ impl Collection {
         // update cubes with next positions.
         self.update_cube_positions();
 
+        // unsupported cubes fall after moving, as a step of their own.
+        self.update_cube_falling();
+
         // do some cleaning.
         self.retain_alive_cube();
+
+        // open or close doors for the next turn.
+        self.update_terrain();
     }
 
-    fn update_cube_status(&mut self) {
+    fn update_cube_status(&mut self, moved: bool) {
+        self.news.clear();
+        self.warp.clear();
         for cube in self.cube.iter_mut() {
             cube.balanced = false;
-            cube.movement = cube.motion.next().unwrap_or_default();
+            cube.movement = cube.motion.step(moved).unwrap_or_default();
             cube.constraint = Constraint::Free;
         }
     }
 
-    fn update_cube_movement(&mut self, movement: Option<Movement>) {
-        const CONTROLED: Kind = Kind::Green;
-        if let Some(movement) = movement {
-            for cube in self.cube.iter_mut().filter(|cube| cube.kind == CONTROLED) {
+    fn update_cube_movement(&mut self, input: Input) {
+        let controlled = |cube: &&mut Cube| self.rule.controlled(cube.kind);
+        for cube in self.cube.iter_mut().filter(controlled) {
+            if let Some(movement) = input.get(cube.kind) {
+                cube.movement = Some(movement);
+            }
+        }
+    }
+
+    fn update_cube_conveying(&mut self) {
+        // conveyors under a cube vote for its movement, and it stays if they
+        // disagree.
+        let area = &self.area;
+        for cube in self.cube.iter_mut() {
+            let mut conveyors = cube
+                .units
+                .iter()
+                .filter_map(|unit| match area.floor(unit.position) {
+                    Some(Floor::Conveyor(movement)) => Some(Some(movement)),
+                    _ => None,
+                })
+                .peekable();
+            if conveyors.peek().is_some() {
+                cube.movement = Agreement::vote(conveyors).unwrap_or_default();
+            }
+        }
+    }
+
+    fn update_cube_sliding(&mut self) {
+        // sliding cubes ignore inputs and commands.
+        for cube in self.cube.iter_mut() {
+            if let Some(movement) = cube.slide {
                 cube.movement = Some(movement);
             }
         }
     }
 
+    fn update_cube_falling(&mut self) {
+        let gravity = match self.fall {
+            Some(gravity) => gravity,
+            None => return,
+        };
+
+        // cubes are held by walls, static cubes, edges, closed floors, or
+        // other held cubes.
+        let alive = self.cube.iter().filter(|cube| cube.alive());
+        let territory = Territory::new(alive.clone());
+        fn below(cube: &Cube, gravity: Movement) -> impl Iterator<Item = Point> + '_ {
+            let anchor = Contours::anchor(&cube.units);
+            cube.contours.one(anchor, gravity)
+        }
+        let mut held = vec![false; self.cube.len()];
+        for cube in alive.clone() {
+            let large = cube.units.len() > 1;
+            held[cube.index] = below(cube, gravity).any(|o| self.stops(o, gravity, large));
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for cube in alive.clone() {
+                let on = |o| territory.get(o).is_some_and(|other| held[other.index]);
+                if !held[cube.index] && below(cube, gravity).any(on) {
+                    held[cube.index] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        // others fall, unless they have just moved along gravity.
+        let falling = (self.cube.iter())
+            .map(|cube| {
+                let along = cube.moved() && cube.movement == Some(gravity);
+                cube.alive() && !held[cube.index] && !along
+            })
+            .collect::<Vec<_>>();
+        if !falling.contains(&true) {
+            return;
+        }
+
+        let before = (self.cube.iter())
+            .map(|cube| (cube.movement, cube.constraint, cube.slide))
+            .collect::<Vec<_>>();
+        for cube in self.cube.iter_mut() {
+            cube.movement = falling[cube.index].then_some(gravity);
+            cube.constraint = Constraint::Free;
+        }
+        let news = self.news.len();
+        let successors = self.process_blocked_cubes();
+        let competed = self.process_conflicted_cubes(&successors);
+        self.process_competed_cubes(&successors, competed);
+        self.update_cube_positions();
+
+        // a cube that fails to fall just stays, so only links are news, and
+        // cubes except those that fell keep what they did in this turn.
+        let mut fallen = self.news.split_off(news);
+        fallen.retain(|event| matches!(event, Event::Merge { .. } | Event::Absorb { .. }));
+        self.news.append(&mut fallen);
+        for cube in self.cube.iter_mut() {
+            if !falling[cube.index] || cube.constraint != Constraint::Free {
+                (cube.movement, cube.constraint, cube.slide) = before[cube.index];
+            }
+        }
+    }
+
     fn update_cube_positions(&mut self) {
+        // units crossing edges of a wrapping board warp to the other side.
+        let board = self.area.board();
         for cube in self.cube.iter_mut() {
             if cube.constraint == Constraint::Free {
                 if let Some(movement) = cube.movement {
                     let direction = movement.into();
                     for unit in cube.units.iter_mut() {
-                        unit.position += direction;
+                        let position = board.wrap(unit.position + direction);
+                        if position != unit.position + direction {
+                            self.warp.push((unit.index, unit.position));
+                        }
+                        unit.position = position;
+                    }
+                }
+            }
+        }
+
+        // teleport single units that moved onto portals, if exits are free.
+        let occupied = (self.cube.iter())
+            .flat_map(|cube| cube.units.iter().map(|unit| unit.position))
+            .collect::<HashSet<_>>();
+        for cube in self.cube.iter_mut().filter(|cube| cube.moved()) {
+            if let (Some(movement), [unit]) = (cube.movement, cube.units.as_mut_slice()) {
+                if let Some(Floor::Portal(exit)) = self.area.floor(unit.position) {
+                    let blocked = self.area.blocked(exit) || self.land.blocked(exit);
+                    if !occupied.contains(&exit) && !blocked {
+                        let source = board.wrap(unit.position - Point::from(movement));
+                        self.warp.retain(|&(index, _)| index != unit.index);
+                        self.warp.push((unit.index, source));
+                        unit.position = exit;
                     }
                 }
             }
         }
+
+        // keep sliding if it moves and then stands on ice.
+        let area = &self.area;
+        for cube in self.cube.iter_mut() {
+            let icy = |unit: &Unit| area.floor(unit.position) == Some(Floor::Ice);
+            cube.slide = match cube.moved() && cube.units.iter().all(icy) {
+                true => cube.movement,
+                false => None,
+            };
+        }
+    }
+
+    fn update_terrain(&mut self) {
+        if self.land.is_empty() {
+            return;
+        }
+        let occupied = (self.cube.iter())
+            .flat_map(|cube| cube.units.iter().map(|unit| unit.position))
+            .collect::<HashSet<_>>();
+        let area = &self.area;
+        self.land
+            .update(|o| occupied.contains(&o) || area.occupied(o));
     }
 
     fn process_imbalanced_cubes(&mut self) {
         // prepare to connect
         let number_of_cubes = self.cube.len();
-        let unstable = self.cube.iter().filter(|u| u.alive() && u.unstable());
+        let unstable = self
+            .cube
+            .iter()
+            .filter(|u| u.alive() && u.unstable(&self.rule));
 
         let territory = Territory::new(unstable.clone());
         let mut connection = DisjointSet::new(number_of_cubes);
@@ -170,7 +680,7 @@ impl Collection {
             queue.push_back(cube);
             while let Some(other) = queue.pop_front() {
                 for other in territory.neighbors(other) {
-                    if cube.absorbable(other) {
+                    if cube.absorbable(other, &self.rule) {
                         if !visit[other.index] {
                             visit[other.index] = true;
                             queue.push_back(other);
@@ -183,7 +693,7 @@ impl Collection {
 
         // try to absorb each others.
         for group in connection.groups() {
-            let mut arena = Arena::new();
+            let mut arena = Arena::new(&self.rule);
             for &index in group.iter() {
                 if !arena.input(self.cube[index].kind) {
                     break;
@@ -206,27 +716,43 @@ impl Collection {
 
         // find blocked and marks them with Constraint::Stop.
         let territory = Territory::new(self.cube.iter());
-        let mut stopped = Vec::new();
+        let mut stopped = HashMap::new();
         for cube in self.cube.iter().filter_map(Moving::new) {
-            let mut blocked = cube.frontlines().any(|o| self.area.blocked(o));
+            let large = cube.cube.units.len() > 1;
+            let mut blocked = cube
+                .frontlines()
+                .any(|o| self.stops(o, cube.movement, large))
+                .then_some(Reason::Wall);
 
-            if !blocked {
+            if blocked.is_none() {
                 let neighbors = territory.neighbors_in_front(&cube).collect::<HashSet<_>>();
                 blocked = neighbors
                     .iter()
-                    .any(|&other| !cube.same_movement(other) && !cube.linkable(other));
-
-                if !blocked {
-                    for &other in neighbors.iter() {
-                        if !cube.same_movement(other) && cube.linkable(other) {
-                            blocked = true;
-                            stopped.push(other.index);
-                            connection.join(&cube, other);
-                        }
+                    .filter(|&&other| {
+                        !cube.same_movement(other) && !cube.linkable(other, &self.rule)
+                    })
+                    .map(|other| other.index)
+                    .min()
+                    .map(Reason::Cube);
+
+                if blocked.is_none() {
+                    let mut linked = neighbors
+                        .iter()
+                        .filter(|&&other| {
+                            !cube.same_movement(other) && cube.linkable(other, &self.rule)
+                        })
+                        .collect::<Vec<_>>();
+                    linked.sort_unstable_by_key(|other| other.index);
+                    for &&other in linked.iter() {
+                        stopped
+                            .entry(other.index)
+                            .or_insert(Reason::Link(cube.index));
+                        connection.join(&cube, other);
                     }
+                    blocked = linked.first().map(|other| Reason::Link(other.index));
                 }
 
-                if !blocked {
+                if blocked.is_none() {
                     for &other in neighbors.iter() {
                         if cube.same_movement(other) {
                             successors.add(other, &cube);
@@ -235,19 +761,18 @@ impl Collection {
                 }
             }
 
-            if blocked {
-                stopped.push(cube.index);
+            if let Some(reason) = blocked {
+                stopped.insert(cube.index, reason);
             }
         }
 
-        self.conduct(
-            stopped,
+        let stopped_cubes = self.conduct(
+            stopped.keys().copied(),
             &successors,
             Constraint::Stop,
             Some(&mut connection),
-        )
-        .into_iter()
-        .for_each(|index| self.cube[index].constraint = Constraint::Stop);
+        );
+        self.constrain(stopped_cubes, &stopped, Constraint::Stop);
         self.link(&mut connection);
 
         successors
@@ -263,7 +788,7 @@ impl Collection {
             .filter_map(Moving::new)
             .for_each(|cube| conflict.put(&cube, cube.movement, cube.frontlines()));
 
-        let mut locked = HashSet::with_capacity(number_of_cubes);
+        let mut locked = HashMap::with_capacity(number_of_cubes);
         let mut competed = HashSet::with_capacity(number_of_cubes);
         for race in conflict.overlaps() {
             let cube = &self.cube;
@@ -271,21 +796,24 @@ impl Collection {
             let half = size >> 1;
             for i in 0..race.len() {
                 let it = match race[i] {
-                    Some(index) if !locked.contains(&index) => index,
+                    Some(index) if !locked.contains_key(&index) => index,
                     _ => continue,
                 };
 
                 let prev = race[(i + size - 1) % size];
                 let next = race[(i + /* **/ 1) % size];
-                if Conflict::locked(cube, it, prev) || Conflict::locked(cube, it, next) {
-                    locked.insert(it);
+                let mut rivals = [prev, next].into_iter().flatten();
+                if let Some(other) =
+                    rivals.find(|&o| Conflict::locked(cube, &self.rule, it, Some(o)))
+                {
+                    locked.insert(it, Reason::Race(other));
                     continue;
                 }
 
                 if let Some(oppo) = race[(i + half) % size] {
-                    let (lhs, rhs) = if cube[it].absorbable(&cube[oppo]) {
+                    let (lhs, rhs) = if cube[it].absorbable(&cube[oppo], &self.rule) {
                         (it, oppo)
-                    } else if cube[oppo].absorbable(&cube[it]) {
+                    } else if cube[oppo].absorbable(&cube[it], &self.rule) {
                         (oppo, it)
                     } else {
                         (it.min(oppo), it.max(oppo))
@@ -295,9 +823,13 @@ impl Collection {
             }
         }
 
-        self.conduct(locked, &successors, Constraint::Lock, Some(&mut connection))
-            .into_iter()
-            .for_each(|index| self.cube[index].constraint = Constraint::Lock);
+        let locked_cubes = self.conduct(
+            locked.keys().copied(),
+            successors,
+            Constraint::Lock,
+            Some(&mut connection),
+        );
+        self.constrain(locked_cubes, &locked, Constraint::Lock);
         self.link(&mut connection);
 
         competed
@@ -309,7 +841,10 @@ impl Collection {
 
         // prepare to rebalance
         let number_of_cubes = self.cube.len();
-        let unstable = self.cube.iter().filter(|u| u.alive() && u.unstable());
+        let unstable = self
+            .cube
+            .iter()
+            .filter(|u| u.alive() && u.unstable(&self.rule));
         let territory = QuarterTerritory::new(unstable.clone());
         let mut connection = DisjointSet::new(number_of_cubes);
 
@@ -320,7 +855,7 @@ impl Collection {
             queue.push_back(cube);
             while let Some(other) = queue.pop_front() {
                 for other in territory.neighbors(other) {
-                    if cube.absorbable(other) {
+                    if cube.absorbable(other, &self.rule) {
                         if !visit[other.index] {
                             visit[other.index] = true;
                             queue.push_back(other);
@@ -332,9 +867,9 @@ impl Collection {
         }
 
         // absorbable testes
-        let mut loser = HashSet::new();
+        let mut loser = HashMap::new();
         for group in connection.groups() {
-            let mut arena = Arena::new();
+            let mut arena = Arena::new(&self.rule);
             for &index in group.iter() {
                 if !arena.input(self.cube[index].kind) {
                     break;
@@ -351,13 +886,15 @@ impl Collection {
                         .map(|c| c.movement);
 
                     if let Some(movement) = Agreement::vote(iter).unwrap_or_default() {
-                        for index in group {
-                            let cube = &mut self.cube[index];
+                        let winner = group.iter().find(|&&i| self.cube[i].kind == kind);
+                        let reason = Reason::Cube(winner.copied().unwrap_or_default());
+                        for &index in group.iter() {
+                            let cube = &self.cube[index];
                             if cube.kind != kind
                                 && cube.constraint < Constraint::Slap
                                 && cube.movement != Some(movement)
                             {
-                                loser.insert(index);
+                                loser.entry(index).or_insert(reason);
                             }
                         }
                     }
@@ -370,16 +907,15 @@ impl Collection {
         for (l, r) in competed {
             let c = &mut self.cube;
             if c[l].constraint < Constraint::Slap && c[r].constraint < Constraint::Slap {
-                loser.insert(r);
-                if !c[l].absorbable(&c[r]) {
-                    loser.insert(l);
+                loser.entry(r).or_insert(Reason::Race(l));
+                if !c[l].absorbable(&c[r], &self.rule) {
+                    loser.entry(l).or_insert(Reason::Race(r));
                 }
             }
         }
 
-        self.conduct(loser, &successors, Constraint::Lock, None)
-            .into_iter()
-            .for_each(|index| self.cube[index].constraint = Constraint::Slap);
+        let slapped_cubes = self.conduct(loser.keys().copied(), successors, Constraint::Lock, None);
+        self.constrain(slapped_cubes, &loser, Constraint::Slap);
     }
 
     fn retain_alive_cube(&mut self) {
@@ -401,14 +937,19 @@ impl Collection {
         successors: &Digraph,
         constraint: Constraint,
         connection: Option<&mut DisjointSet>,
-    ) -> HashSet<usize> {
+    ) -> HashMap<usize, Option<usize>> {
         let number_of_cubes = self.cube.len();
         let mut queue = VecDeque::with_capacity(number_of_cubes);
-        let mut visit = HashSet::with_capacity(number_of_cubes);
+        let mut visit = HashMap::with_capacity(number_of_cubes);
+
+        // note: keep the order so that the precursors are deterministic.
+        let mut determined = determined.into_iter().collect::<Vec<_>>();
+        determined.sort_unstable();
 
-        for index in determined.into_iter() {
+        for index in determined {
             let cube = &self.cube[index];
-            if cube.constraint <= constraint && visit.insert(index) {
+            if cube.constraint <= constraint && !visit.contains_key(&index) {
+                visit.insert(index, None);
                 queue.push_back(cube);
             }
 
@@ -418,12 +959,13 @@ impl Collection {
                     .map(|&index| &self.cube[index])
                     .filter(|cube| cube.constraint <= constraint)
                 {
-                    if visit.insert(successor.index) {
+                    if let Entry::Vacant(entry) = visit.entry(successor.index) {
+                        entry.insert(Some(precursor.index));
                         queue.push_back(successor);
                     }
 
                     if let Some(&mut ref mut connection) = connection {
-                        if precursor.linkable(successor) {
+                        if precursor.linkable(successor, &self.rule) {
                             connection.join(precursor, successor);
                         }
                     }
@@ -434,9 +976,52 @@ impl Collection {
         visit
     }
 
+    // whether a cube moving onto the point is stopped by terrain. Portals only
+    // take single units, and gates only take cubes moving in their directions.
+    fn stops(&self, o: Point, movement: Movement, large: bool) -> bool {
+        let closed = match self.area.floor(o) {
+            Some(Floor::Portal(_)) => large,
+            Some(Floor::Gate(gate)) => gate != movement,
+            _ => false,
+        };
+        closed || self.area.blocked(o) || self.land.blocked(o)
+    }
+
+    fn constrain(
+        &mut self,
+        conducted: HashMap<usize, Option<usize>>,
+        determined: &HashMap<usize, Reason>,
+        constraint: Constraint,
+    ) {
+        let mut conducted = conducted.into_iter().collect::<Vec<_>>();
+        conducted.sort_unstable();
+
+        for (index, precursor) in conducted {
+            let reason = match precursor {
+                Some(precursor) => Reason::Follow(precursor),
+                None => determined[&index],
+            };
+            let id = |i: usize| self.cube[i].ident;
+            let cube = id(index);
+            let reason = match reason {
+                Reason::Wall => Reason::Wall,
+                Reason::Cube(i) => Reason::Cube(id(i)),
+                Reason::Link(i) => Reason::Link(id(i)),
+                Reason::Race(i) => Reason::Race(id(i)),
+                Reason::Follow(i) => Reason::Follow(id(i)),
+            };
+            self.news.push(match constraint {
+                Constraint::Stop => Event::Block { cube, reason },
+                Constraint::Lock => Event::Lock { cube, reason },
+                _ => Event::Slap { cube, reason },
+            });
+            self.cube[index].constraint = constraint;
+        }
+    }
+
     fn link(&mut self, connection: &mut DisjointSet) {
         for group in connection.groups() {
-            let mut arena = Arena::new();
+            let mut arena = Arena::new(&self.rule);
             for &index in group.iter() {
                 arena.input(self.cube[index].kind);
             }
@@ -447,6 +1032,41 @@ impl Collection {
     }
 
     fn merge(&mut self, from: Vec<usize>, kind: Kind) {
+        // the smallest identity of the same kind survives.
+        let mut merged = Vec::with_capacity(from.len());
+        let mut absorbed = Vec::with_capacity(from.len());
+        for &i in from.iter() {
+            if self.cube[i].kind == kind {
+                merged.push(self.cube[i].ident);
+            } else {
+                absorbed.push(self.cube[i].ident);
+            }
+        }
+        merged.sort_unstable();
+        absorbed.sort_unstable();
+        let ident = match merged.first() {
+            Some(&ident) => ident,
+            None => return,
+        };
+        for &other in merged.iter().skip(1).chain(absorbed.iter()) {
+            self.fate[other] = Some(ident);
+        }
+        for &other in absorbed.iter() {
+            self.lost[other] = true;
+        }
+        if merged.len() > 1 {
+            self.news.push(Event::Merge {
+                into: ident,
+                from: merged,
+            });
+        }
+        if !absorbed.is_empty() {
+            self.news.push(Event::Absorb {
+                into: ident,
+                from: absorbed,
+            });
+        }
+
         let cube = &mut self.cube;
 
         let units = {
@@ -455,9 +1075,10 @@ impl Collection {
             for &i in from.iter() {
                 units.append(&mut cube[i].units);
             }
+            let board = self.area.board();
             let collision = HashSetCollision::new(units.iter().map(|unit| unit.position));
             for unit in units.iter_mut() {
-                unit.neighborhood = collision.neighborhood(unit.position);
+                unit.neighborhood = board.neighborhood(&collision, unit.position);
             }
             units
         };
@@ -470,7 +1091,7 @@ impl Collection {
             }
             Motion::from_iter(others.into_iter())
         };
-        let contours = Contours::new(&units).into();
+        let contours = Contours::new(&units, self.area.board()).into();
         let movement = Agreement::vote(
             from.iter()
                 .filter(|&&i| cube[i].kind == kind)
@@ -490,6 +1111,7 @@ impl Collection {
         if let Some(&index) = from.first() {
             cube[index] = Cube {
                 index,
+                ident,
                 kind,
                 units,
                 motion,
@@ -497,17 +1119,93 @@ impl Collection {
                 balanced: false,
                 movement,
                 constraint,
+                slide: None,
             };
         }
     }
 }
 
+/// Image is a canonical copy of everything that affects the following turns
+/// of a collection, i.e. kinds, positions and motions of its cubes. The order
+/// of cubes makes no difference.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Image(Box<[u8]>);
+
+impl Image {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    pub(crate) fn put_points(output: &mut Vec<u8>, points: impl ExactSizeIterator<Item = Point>) {
+        output.extend_from_slice(&(points.len() as u32).to_le_bytes());
+        for o in points {
+            output.extend_from_slice(&o.x.to_le_bytes());
+            output.extend_from_slice(&o.y.to_le_bytes());
+        }
+    }
+
+    // FNV-1a, which is stable across platforms and releases.
+    pub(crate) fn digest(hash: u64, bytes: &[u8]) -> u64 {
+        bytes
+            .iter()
+            .fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(Self::PRIME))
+    }
+}
+
+/// Archive is a plain copy of a collection, which is used to save and restore.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Archive {
+    width: usize,
+    height: usize,
+    wrap: bool,
+    cubes: Vec<ArchivedCube>,
+    frozen: Vec<(usize, Kind, Point, Neighborhood)>,
+    walls: Vec<Point>,
+    floors: Vec<(Point, Floor)>,
+    wires: Vec<(Vec<Point>, Vec<Point>)>,
+    gravity: Option<Movement>,
+    rule: RuleSet,
+    fate: Vec<Option<usize>>,
+    lost: Vec<bool>,
+    turn: usize,
+    moves: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ArchivedCube {
+    ident: usize,
+    kind: Kind,
+    units: Vec<(usize, Point)>,
+    motion: Progress,
+    movement: Option<Movement>,
+    constraint: Constraint,
+    slide: Option<Movement>,
+}
+
+impl PartialEq for Collection {
+    fn eq(&self, other: &Self) -> bool {
+        let same_area = Arc::ptr_eq(&self.area, &other.area) && self.rule == other.rule
+            || self.scenery() == other.scenery();
+        same_area && self.image() == other.image()
+    }
+}
+
+impl Eq for Collection {}
+
+impl std::hash::Hash for Collection {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.fingerprint().hash(state);
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////
 // internal
 
 #[derive(Clone, Debug)]
 struct Cube {
     index: usize,
+    ident: usize, // identity that survives merging
     kind: Kind,
     units: Vec<Unit>,
     motion: Motion,
@@ -515,6 +1213,7 @@ struct Cube {
     balanced: bool,             // state of being unabsorbable
     movement: Option<Movement>, // original movement direction
     constraint: Constraint,     // state of movement
+    slide: Option<Movement>,    // movement that keeps going on ice
 }
 
 impl Cube {
@@ -522,16 +1221,20 @@ impl Cube {
         !self.units.is_empty()
     }
 
-    fn unstable(&self) -> bool {
-        !self.balanced && !matches!(self.kind, Kind::White) && self.alive()
+    fn moved(&self) -> bool {
+        self.constraint == Constraint::Free && self.movement.is_some()
     }
 
-    const fn linkable(&self, other: &Self) -> bool {
-        self.kind.linkable(other.kind)
+    fn unstable(&self, rule: &RuleSet) -> bool {
+        !self.balanced && !rule.is_static(self.kind) && self.alive()
     }
 
-    const fn absorbable(&self, other: &Self) -> bool {
-        !self.balanced && !other.balanced && self.kind.absorbable(other.kind)
+    fn linkable(&self, other: &Self, rule: &RuleSet) -> bool {
+        rule.linkable(self.kind, other.kind)
+    }
+
+    fn absorbable(&self, other: &Self, rule: &RuleSet) -> bool {
+        !self.balanced && !other.balanced && rule.absorbable(self.kind, other.kind)
     }
 
     fn same_movement(&self, other: &Self) -> bool {
@@ -576,10 +1279,11 @@ impl Unit {
 struct Contours {
     count: [usize; 3],
     slice: Box<[Point]>,
+    board: Board,
 }
 
 impl Contours {
-    fn new(units: &[Unit]) -> Self {
+    fn new(units: &[Unit], board: Board) -> Self {
         const LBTR: [Adjacence; 4] = [
             Adjacence::LEFT,
             Adjacence::BOTTOM,
@@ -627,6 +1331,7 @@ impl Contours {
         Self {
             count: [count[0], count[1], count[2]],
             slice: slice.into(),
+            board,
         }
     }
 
@@ -639,11 +1344,11 @@ impl Contours {
             Right /**/ => &self.slice[self.count[2].. /*      **/ ],
         }
         .iter()
-        .map(move |o| anchor - *o)
+        .map(move |o| self.board.wrap(anchor - *o))
     }
 
     fn all(&self, anchor: Point) -> impl Iterator<Item = Point> + Clone + '_ {
-        (&self.slice[..]).iter().map(move |o| anchor - *o)
+        self.slice.iter().map(move |o| self.board.wrap(anchor - *o))
     }
 
     fn anchor(units: &[Unit]) -> Point {
@@ -712,12 +1417,13 @@ impl<'a> QuarterTerritory<'a> {
         for cube in it.clone().map(Into::into) {
             let cube: &Cube = cube;
             let delta = Self::delta(cube);
+            let board = cube.contours.board.doubled();
             for unit in cube.units.iter().filter(|unit| unit.is_border()) {
                 let point = unit.position * 2 + delta;
-                map.insert(point + Point::new(0, 0), cube);
-                map.insert(point + Point::new(0, 1), cube);
-                map.insert(point + Point::new(1, 0), cube);
-                map.insert(point + Point::new(1, 1), cube);
+                map.insert(board.wrap(point + Point::new(0, 0)), cube);
+                map.insert(board.wrap(point + Point::new(0, 1)), cube);
+                map.insert(board.wrap(point + Point::new(1, 0)), cube);
+                map.insert(board.wrap(point + Point::new(1, 1)), cube);
             }
         }
 
@@ -728,6 +1434,7 @@ impl<'a> QuarterTerritory<'a> {
         let cube: &'a Cube = cube.into();
         let delta = Self::delta(cube);
         let anchor = Contours::anchor(&cube.units);
+        let board = cube.contours.board.doubled();
         Movement::ALL.into_iter().flat_map(move |movement| {
             cube.contours
                 .one(anchor, movement)
@@ -739,7 +1446,7 @@ impl<'a> QuarterTerritory<'a> {
                     Movement::Down /* **/ => [Point::new(0, 0), Point::new(1, 0)],
                     Movement::Up /*   **/ => [Point::new(0, 1), Point::new(1, 1)],
                     Movement::Right /***/ => [Point::new(0, 0), Point::new(0, 1)]}
-                    .map(|x| point + x)
+                    .map(|x| board.wrap(point + x))
                     .into_iter()
                     .filter_map(|point| self.0.get(&point))
                     .cloned()
@@ -786,15 +1493,18 @@ impl Conflict {
             .collect()
     }
 
-    const fn locked(cube: &[Cube], this: usize, other: Option<usize>) -> bool {
+    fn locked(cube: &[Cube], rule: &RuleSet, this: usize, other: Option<usize>) -> bool {
         match other {
-            Some(other) => !cube[this].absorbable(&cube[other]),
+            Some(other) => !cube[this].absorbable(&cube[other], rule),
             None /*__*/ => false,
         }
     }
 }
 
-struct Arena([bool; 3]);
+struct Arena<'a> {
+    rule: &'a RuleSet,
+    kinds: u32,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 enum ArenaResult {
@@ -804,45 +1514,40 @@ enum ArenaResult {
     None,
 }
 
-impl Arena {
-    fn new() -> Self {
-        Self([false; 3])
+impl<'a> Arena<'a> {
+    fn new(rule: &'a RuleSet) -> Self {
+        Self { rule, kinds: 0 }
     }
 
     fn input(&mut self, kind: Kind) -> bool {
-        if let Some(index) = Self::kind_to_index(kind) {
-            self.0[index] = true;
-        };
-        !(self.0[0] && self.0[1] && self.0[2])
+        if !self.rule.is_static(kind) {
+            self.kinds |= kind.bit();
+        }
+        Kind::ALL.into_iter().any(|kind| self.dominates(kind))
     }
 
     fn output(&self) -> ArenaResult {
-        use ArenaResult::*;
-        use Kind::*;
-        match ((self.0[0] as usize) << 2) // R
-            | ((self.0[1] as usize) << 1) // B
-            | ((self.0[2] as usize) << 0) // G
-        { //  RGB
-            0b111 => Draw,
-            0b101 => Have(Red),
-            0b110 => Have(Blue),
-            0b011 => Have(Green),
-            0b001 => Pure(Green),
-            0b010 => Pure(Blue),
-            0b100 => Pure(Red),
-            _else => None,
-        }
-    }
-
-    const fn kind_to_index(kind: Kind) -> Option<usize> {
-        use Kind::*;
-        match kind {
-            White => None,
-            Red => Some(0),
-            Blue => Some(1),
-            Green => Some(2),
+        let mut kinds = Kind::ALL.into_iter().filter(|&kind| self.has(kind));
+        let mut winners = kinds.clone().filter(|&kind| self.dominates(kind));
+        match (kinds.next(), kinds.next(), winners.next(), winners.next()) {
+            (None, ..) => ArenaResult::None,
+            (Some(kind), None, ..) => ArenaResult::Pure(kind),
+            (_, _, Some(kind), None) => ArenaResult::Have(kind),
+            _ => ArenaResult::Draw,
         }
     }
+
+    fn has(&self, kind: Kind) -> bool {
+        self.kinds & kind.bit() != 0
+    }
+
+    // whether it is able to absorb all other kinds.
+    fn dominates(&self, kind: Kind) -> bool {
+        Kind::ALL
+            .into_iter()
+            .filter(|&other| other != kind && self.has(other))
+            .all(|other| self.rule.absorbable(kind, other))
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -904,6 +1609,12 @@ impl<'a> std::ops::Deref for Moving<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        objective::Outcome,
+        seed::{Command, Cube, Seed, Wire},
+        solver::{solve, Solution},
+        CubeCore, Diff,
+    };
 
     #[test]
     fn arena() {
@@ -943,20 +1654,44 @@ mod tests {
                 ],
                 ArenaResult::Draw,
             ),
+            (
+                vec![
+                    (Kind::Yellow, true),
+                    (Kind::Blue, true),
+                    (Kind::Green, true),
+                ],
+                ArenaResult::Have(Kind::Green),
+            ),
+            (
+                vec![(Kind::Purple, true), (Kind::Purple, true)],
+                ArenaResult::Pure(Kind::Purple),
+            ),
         ];
 
         for (input, output) in cases {
-            let mut arena = Arena::new();
+            let rule = RuleSet::default();
+            let mut arena = Arena::new(&rule);
             for (kind, value) in input {
                 assert_eq!(arena.input(kind), value);
             }
             assert_eq!(arena.output(), output);
         }
+
+        // red takes all without cycles.
+        let rule = RuleSet::new()
+            .absorb(Kind::Red, Kind::Green)
+            .absorb(Kind::Red, Kind::Blue);
+        let mut arena = Arena::new(&rule);
+        assert!(arena.input(Kind::Green));
+        assert!(arena.input(Kind::Blue));
+        assert_eq!(arena.output(), ArenaResult::Draw);
+        assert!(arena.input(Kind::Red));
+        assert_eq!(arena.output(), ArenaResult::Have(Kind::Red));
     }
 
     #[test]
     fn contours() {
-        let contours = Contours::new(&[]);
+        let contours = Contours::new(&[], Board::default());
         let actual = Vec::from_iter(contours.all(Point::new(0, 0)));
         assert_eq!(actual, Vec::new());
         for movement in Movement::ALL {
@@ -976,7 +1711,7 @@ mod tests {
                 neighborhood: Neighborhood::from([Adjacence::TOP].into_iter()),
             },
         ];
-        let contours = Contours::new(&units);
+        let contours = Contours::new(&units, Board::default());
 
         let expected = vec![Point::new(0, 1), Point::new(0, 2)];
         let actual = Vec::from_iter(contours.one(Point::new(1, 1), Movement::Left));
@@ -994,4 +1729,608 @@ mod tests {
         let actual = Vec::from_iter(contours.one(Point::new(1, 1), Movement::Down));
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn events() {
+        /*******
+         *G B W*
+         *     *
+         *B B  *
+         *******/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Blue, Point::new(2, 0)),
+                Cube::unit(Kind::White, Point::new(4, 0)),
+                Cube {
+                    kind: Kind::Blue,
+                    body: vec![Point::new(0, 2)],
+                    command: Some(Command {
+                        is_loop: true,
+                        movements: vec![(Some(Movement::Right), 1)],
+                    }),
+                },
+                Cube::unit(Kind::Blue, Point::new(2, 2)),
+            ],
+            ..Seed::sized(5, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        assert!(game.events().is_empty());
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert!(game.events().is_empty());
+
+        // green absorbs blue, and blue cubes merge after hitting each other.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        let turn = vec![
+            Event::Absorb {
+                into: 0,
+                from: vec![1],
+            },
+            Event::Block {
+                cube: 3,
+                reason: Reason::Link(4),
+            },
+            Event::Block {
+                cube: 4,
+                reason: Reason::Link(3),
+            },
+            Event::Merge {
+                into: 3,
+                from: vec![3, 4],
+            },
+        ];
+        assert_eq!(game.events(), turn);
+        assert_eq!(game.absorbed_into(0), None);
+        assert_eq!(game.absorbed_into(1), Some(0));
+        assert_eq!(game.absorbed_into(4), Some(3));
+        for unit in game.iter() {
+            match unit.kind {
+                Kind::Green => assert_eq!(unit.cube, 0),
+                Kind::Blue => assert_eq!(unit.cube, 3),
+                _ => assert_eq!(unit.cube, 2),
+            }
+        }
+
+        game.commit(Some(Movement::Right)).for_each(drop);
+        let wall = [Event::Block {
+            cube: 0,
+            reason: Reason::Wall,
+        }];
+        assert_eq!(game.events(), wall);
+
+        game.undo().for_each(drop);
+        assert_eq!(game.events(), turn);
+        game.redo().for_each(drop);
+        assert_eq!(game.events(), wall);
+    }
+
+    #[test]
+    fn rule_set() {
+        /*****
+         *G R*
+         *   *
+         *B  *
+         *****/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Red, Point::new(2, 0)),
+                Cube::unit(Kind::Blue, Point::new(0, 2)),
+            ],
+            ..Seed::sized(3, 3)
+        };
+
+        // both green and blue are controlled, and only red absorbs others.
+        let rules = RuleSet::new()
+            .control(Kind::Green)
+            .control(Kind::Blue)
+            .absorb(Kind::Red, Kind::Green)
+            .absorb(Kind::Red, Kind::Blue);
+        let mut game = CubeCore::new(&seed, &rules);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        game.commit(None).for_each(drop);
+
+        let units = game.iter().collect::<Vec<_>>();
+        assert_eq!(units[0].kind, Kind::Red);
+        assert_eq!(units[0].position, Point::new(1, 0));
+        assert_eq!(units[2].kind, Kind::Blue);
+        assert_eq!(units[2].position, Point::new(1, 2));
+
+        // the default one is different.
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        game.commit(Some(Movement::Right)).for_each(drop);
+        let units = game.iter().collect::<Vec<_>>();
+        assert_eq!(units[2].position, Point::new(0, 2));
+        let other = CubeCore::new(&seed, &rules);
+        assert_ne!(
+            other.fingerprint(),
+            CubeCore::new(&seed, &RuleSet::default()).fingerprint()
+        );
+
+        // static cubes keep their kinds, and cover demands of them.
+        let seed = Seed {
+            demands: vec![(Point::new(2, 0), Kind::Red)],
+            ..seed
+        };
+        let rules = RuleSet::default().fix(Kind::Red);
+        let game = CubeCore::new(&seed, &rules);
+        let red = game.iter().find(|unit| unit.position == Point::new(2, 0));
+        assert_eq!(red.map(|unit| unit.kind), Some(Kind::Red));
+        assert!(game.goals().all(|(_, _, covered)| covered));
+        assert!(!game.dead_end(0));
+        let mut other = CubeCore::new(&seed, &RuleSet::default().fix(Kind::Blue));
+        other.commit(Some(Movement::Right)).for_each(drop);
+        assert_ne!(game.fingerprint(), other.fingerprint());
+    }
+
+    #[test]
+    fn walls() {
+        /*****
+         *G #*
+         *****/
+
+        let mut seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            destnations: vec![Point::new(1, 0)],
+            walls: vec![Point::new(2, 0)],
+            ..Seed::sized(3, 1)
+        };
+
+        // walls block cubes, but they are not cubes.
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        assert_eq!(game.iter().count(), 1);
+        assert_eq!(
+            game.walls().map(|(o, _)| o).collect::<Vec<_>>(),
+            vec![Point::new(2, 0)]
+        );
+        game.commit(Some(Movement::Right)).for_each(drop);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(
+            game.iter().next().map(|unit| unit.position),
+            Some(Point::new(1, 0))
+        );
+        assert_eq!(game.outcome(), Outcome::Complete);
+
+        // and they survive a save.
+        let restored = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!(restored.fingerprint(), game.fingerprint());
+
+        // a wall never covers a destination.
+        seed.destnations = vec![Point::new(2, 0)];
+        let game = CubeCore::new(&seed, &RuleSet::default());
+        assert_eq!(
+            game.goals().map(|(_, _, o)| o).collect::<Vec<_>>(),
+            vec![false]
+        );
+        assert!(game.dead_end(0));
+    }
+
+    #[test]
+    fn ice() {
+        /******
+         *G~~~*
+         ******/
+
+        let seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            destnations: vec![Point::new(0, 0)],
+            floors: (1..4).map(|x| (Point::new(x, 0), Floor::Ice)).collect(),
+            ..Seed::sized(4, 1)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let step = |game: &mut CubeCore, movement| {
+            game.commit(movement).for_each(drop);
+            game.iter().next().map(|unit| unit.position.x)
+        };
+
+        // it keeps sliding and ignores inputs on ice.
+        assert_eq!(step(&mut game, Some(Movement::Right)), Some(1));
+        assert_eq!(step(&mut game, Some(Movement::Left)), Some(2));
+
+        // and sliding survives a save.
+        let mut game = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!(step(&mut game, None), Some(3));
+
+        // until it is blocked.
+        assert_eq!(step(&mut game, None), Some(3));
+        assert_eq!(step(&mut game, None), Some(3));
+
+        // it stops when leaving ice.
+        assert_eq!(step(&mut game, Some(Movement::Left)), Some(2));
+        assert_eq!(step(&mut game, None), Some(1));
+        assert_eq!(step(&mut game, None), Some(0));
+        assert_eq!(step(&mut game, None), Some(0));
+    }
+
+    #[test]
+    fn conveyors() {
+        /*******
+         *B-   *
+         *     *
+         *R-   *
+         *******/
+
+        use Movement::*;
+        let seed = Seed {
+            cubes: vec![
+                Cube {
+                    kind: Kind::Blue,
+                    body: vec![Point::new(0, 0), Point::new(1, 0)],
+                    command: None,
+                },
+                Cube {
+                    kind: Kind::Red,
+                    body: vec![Point::new(0, 2), Point::new(1, 2)],
+                    command: None,
+                },
+            ],
+            floors: vec![
+                (Point::new(0, 0), Floor::Conveyor(Right)),
+                (Point::new(1, 0), Floor::Conveyor(Right)),
+                (Point::new(0, 2), Floor::Conveyor(Right)),
+                (Point::new(1, 2), Floor::Conveyor(Left)),
+            ],
+            ..Seed::sized(5, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let xs = |game: &CubeCore, kind| {
+            let mut xs = (game.iter())
+                .filter(|unit| unit.kind == kind)
+                .map(|unit| unit.position.x)
+                .collect::<Vec<_>>();
+            xs.sort_unstable();
+            xs
+        };
+
+        // a cube moves as long as any unit stands on conveyors.
+        game.commit(None).for_each(drop);
+        assert_eq!(xs(&game, Kind::Blue), vec![1, 2]);
+        game.commit(None).for_each(drop);
+        assert_eq!(xs(&game, Kind::Blue), vec![2, 3]);
+        game.commit(None).for_each(drop);
+        assert_eq!(xs(&game, Kind::Blue), vec![2, 3]);
+
+        // and it stays if conveyors disagree.
+        assert_eq!(xs(&game, Kind::Red), vec![0, 1]);
+    }
+
+    #[test]
+    fn portals() {
+        /*******
+         *G1   *
+         *     *
+         *   1 *
+         *******/
+
+        let mut seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            floors: vec![
+                (Point::new(1, 0), Floor::Portal(Point::new(3, 2))),
+                (Point::new(3, 2), Floor::Portal(Point::new(1, 0))),
+            ],
+            ..Seed::sized(5, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let position = |game: &CubeCore| game.iter().next().map(|unit| unit.position);
+        let warp = |diffs: Vec<Diff>| diffs.iter().any(|diff| diff.warp);
+
+        // a single unit appears at the exit.
+        assert!(warp(game.commit(Some(Movement::Right)).collect()));
+        assert_eq!(position(&game), Some(Point::new(3, 2)));
+        assert!(!warp(game.commit(Some(Movement::Left)).collect()));
+        assert_eq!(position(&game), Some(Point::new(2, 2)));
+
+        // and so do both ways of undo and redo.
+        assert!(!warp(game.undo().collect()));
+        assert!(warp(game.undo().collect()));
+        assert_eq!(position(&game), Some(Point::new(0, 0)));
+        assert!(warp(game.redo().collect()));
+
+        // a larger cube is blocked.
+        seed.cubes[0].body.push(Point::new(0, 1));
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        assert!(!warp(game.commit(Some(Movement::Right)).collect()));
+        assert_eq!(position(&game), Some(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn doors() {
+        /******
+         *G_  *
+         *    *
+         *G+  *
+         ******/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Green, Point::new(0, 2)),
+            ],
+            wires: vec![Wire {
+                plates: vec![Point::new(1, 0)],
+                doors: vec![Point::new(1, 2)],
+            }],
+            ..Seed::sized(4, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let xs = |game: &CubeCore| {
+            let mut xs = game.iter().map(|unit| (unit.position.y, unit.position.x));
+            (xs.next().unwrap(), xs.next().unwrap())
+        };
+        let open = |game: &CubeCore| game.doors().all(|(_, open)| open);
+
+        // a closed door blocks, until a cube presses the plate.
+        assert!(!open(&game));
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), ((0, 1), (2, 0)));
+        assert!(open(&game));
+
+        // it stays open while a cube stands in it.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), ((0, 2), (2, 1)));
+        assert!(open(&game));
+
+        // and it closes after that.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), ((0, 3), (2, 2)));
+        assert!(!open(&game));
+
+        // the state of doors is restored from positions.
+        game.undo().for_each(drop);
+        assert!(open(&game));
+        assert!(open(&CubeCore::restore(&game.save()).unwrap()));
+    }
+
+    #[test]
+    fn gates() {
+        /*****
+         *G→ *
+         *****/
+
+        let seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            floors: vec![(Point::new(1, 0), Floor::Gate(Movement::Right))],
+            ..Seed::sized(3, 1)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let step = |game: &mut CubeCore, movement| {
+            let diffs = game.commit(Some(movement)).collect::<Vec<_>>();
+            let unit = game.iter().next().unwrap();
+            (unit.position.x, diffs[0].constraint)
+        };
+
+        // it only lets cubes in one way.
+        assert_eq!(step(&mut game, Movement::Right), (1, None));
+        assert_eq!(step(&mut game, Movement::Right), (2, None));
+        assert_eq!(step(&mut game, Movement::Left), (2, Some(Constraint::Stop)));
+    }
+
+    #[test]
+    fn wrap() {
+        /*****
+         *GG *
+         * # *
+         *****/
+
+        let mut seed = Seed {
+            cubes: vec![Cube {
+                kind: Kind::Green,
+                body: vec![Point::new(0, 0), Point::new(1, 0)],
+                command: None,
+            }],
+            walls: vec![Point::new(1, 1)],
+            wrap: true,
+            ..Seed::sized(3, 2)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let xs = |game: &CubeCore| {
+            let mut xs = game.iter().map(|unit| unit.position).collect::<Vec<_>>();
+            xs.sort_unstable_by_key(|o| (o.y, o.x));
+            xs
+        };
+
+        // walls across the edge still block.
+        game.commit(Some(Movement::Up)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(0, 0), Point::new(1, 0)]);
+
+        // a cube leaving the right edge comes back from the left, and only
+        // the unit crossing the edge warps.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        let diffs = game.commit(Some(Movement::Right)).collect::<Vec<_>>();
+        assert_eq!(xs(&game), vec![Point::new(0, 0), Point::new(2, 0)]);
+        assert_eq!(diffs.iter().filter(|diff| diff.warp).count(), 1);
+        assert!(game
+            .iter()
+            .all(|unit| unit.neighborhood.has(Adjacence::LEFT) ^ (unit.position.x == 2)));
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(0, 0), Point::new(1, 0)]);
+
+        // and it survives a save.
+        let restored = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!(restored.fingerprint(), game.fingerprint());
+
+        // edges block on a board that does not wrap.
+        seed.wrap = false;
+        let mut other = CubeCore::new(&seed, &RuleSet::default());
+        assert_ne!(other.fingerprint(), game.fingerprint());
+        other.commit(Some(Movement::Left)).for_each(drop);
+        assert_eq!(xs(&other), vec![Point::new(0, 0), Point::new(1, 0)]);
+    }
+
+    #[test]
+    fn gravity() {
+        /****
+         *G *
+         *G *
+         *  *
+         ****/
+
+        let green = |o| Cube::unit(Kind::Green, o);
+        let mut seed = Seed {
+            cubes: vec![green(Point::new(0, 0)), green(Point::new(0, 1))],
+            gravity: Some(Movement::Down),
+            ..Seed::sized(2, 3)
+        };
+
+        let rule = RuleSet::new().control(Kind::Green);
+        let mut game = CubeCore::new(&seed, &rule);
+        let xs = |game: &CubeCore| {
+            let mut xs = game.iter().map(|unit| unit.position).collect::<Vec<_>>();
+            xs.sort_unstable_by_key(|o| (o.y, o.x));
+            xs
+        };
+
+        // cubes move and then fall until the edge holds them.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(1, 1), Point::new(1, 2)]);
+        game.commit(None).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(1, 1), Point::new(1, 2)]);
+
+        // a cube on a held cube is held too.
+        game.commit(Some(Movement::Left)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(0, 1), Point::new(0, 2)]);
+
+        // and gravity survives a save.
+        let restored = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!(restored.fingerprint(), game.fingerprint());
+
+        // holding a key never keeps a cube in the air.
+        seed.cubes = vec![green(Point::new(0, 0))];
+        let mut game = CubeCore::new(&seed, &rule);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(1, 1)]);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(1, 2)]);
+
+        // and inputs along falling don't make it faster.
+        let mut game = CubeCore::new(&seed, &rule);
+        game.commit(Some(Movement::Down)).for_each(drop);
+        assert_eq!(xs(&game), vec![Point::new(0, 1)]);
+
+        // nothing falls without gravity.
+        seed.gravity = None;
+        let mut other = CubeCore::new(&seed, &rule);
+        assert_ne!(other.fingerprint(), game.fingerprint());
+        other.commit(None).for_each(drop);
+        assert_eq!(xs(&other), vec![Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn gravity_events() {
+        /****
+         *G#*
+         *  *
+         *↑ *
+         ****/
+
+        let seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            walls: vec![Point::new(1, 0)],
+            floors: vec![(Point::new(0, 2), Floor::Gate(Movement::Up))],
+            gravity: Some(Movement::Down),
+            ..Seed::sized(2, 3)
+        };
+
+        // a blocked cube still falls, and it is blocked only once.
+        let rule = RuleSet::new().control(Kind::Green);
+        let mut game = CubeCore::new(&seed, &rule);
+        game.commit(Some(Movement::Right)).for_each(drop);
+        let block = [Event::Block {
+            cube: 0,
+            reason: Reason::Wall,
+        }];
+        assert_eq!(game.events(), block);
+        let unit = game.iter().next().unwrap();
+        assert_eq!(unit.position, Point::new(0, 1));
+        assert_eq!(unit.constraint, Constraint::Free);
+
+        // a gate against gravity holds a cube like a wall, without news.
+        let diffs = game.commit(None).collect::<Vec<_>>();
+        assert!(game.events().is_empty());
+        assert!(diffs.iter().all(|diff| diff.position.is_none()));
+        let unit = game.iter().next().unwrap();
+        assert_eq!(unit.position, Point::new(0, 1));
+        assert_eq!(unit.constraint, Constraint::Free);
+    }
+
+    #[test]
+    fn players() {
+        /*****
+         *G  *
+         *   *
+         *B  *
+         *****/
+
+        let mut seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Blue, Point::new(0, 2)),
+            ],
+            players: vec![Kind::Green, Kind::Blue],
+            ..Seed::sized(3, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let at = |game: &CubeCore, kind: Kind| {
+            let mut it = game.iter().filter(|unit| unit.kind == kind);
+            it.next().map(|unit| unit.position)
+        };
+
+        // each player steers its own kind.
+        let input = Input::new()
+            .set(Kind::Green, Some(Movement::Right))
+            .set(Kind::Blue, Some(Movement::Up));
+        game.commit(input).for_each(drop);
+        assert_eq!(at(&game, Kind::Green), Some(Point::new(1, 0)));
+        assert_eq!(at(&game, Kind::Blue), Some(Point::new(0, 1)));
+
+        // and a single movement steers all of them.
+        game.commit(Some(Movement::Right)).for_each(drop);
+        assert_eq!(at(&game, Kind::Green), Some(Point::new(2, 0)));
+        assert_eq!(at(&game, Kind::Blue), Some(Point::new(1, 1)));
+        game.commit(Input::new()).for_each(drop);
+        assert_eq!(game.moves(), 2);
+
+        // kinds that no one plays ignore inputs.
+        seed.players = vec![Kind::Green];
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        game.commit(input).for_each(drop);
+        assert_eq!(at(&game, Kind::Green), Some(Point::new(1, 0)));
+        assert_eq!(at(&game, Kind::Blue), Some(Point::new(0, 2)));
+
+        // searches try each player on its own, where a single movement for
+        // both never covers the destinations.
+        seed.players = vec![Kind::Green, Kind::Blue];
+        seed.destnations = vec![Point::new(2, 0), Point::new(0, 2)];
+        let rules = RuleSet::new();
+        let solution = solve(&seed, &rules, 1000);
+        let Solution::Found(inputs) = solution else {
+            panic!("unexpected {:?}", solution);
+        };
+        assert_eq!(inputs.len(), 2);
+        assert!(inputs.iter().all(|input| input.get(Kind::Blue).is_none()));
+
+        let mut game = CubeCore::new(&seed, &rules);
+        assert!(!game.dead_end(2000));
+        let hint = game.hint().unwrap();
+        assert_eq!(hint.get(Kind::Green), Some(Movement::Right));
+        assert_eq!(hint.get(Kind::Blue), None);
+        inputs
+            .into_iter()
+            .for_each(|input| game.commit(input).for_each(drop));
+        assert_eq!(game.outcome(), Outcome::Complete);
+
+        // and saves keep players.
+        let restored = CubeCore::restore(&game.save()).unwrap();
+        assert_eq!(restored.save(), game.save());
+    }
 }
@@ -1,45 +1,144 @@
 use super::{
-    extension::CollisionExtension,
+    board::Board,
     lookup::{BitmapCollision, Collision},
 };
-use crate::cube::{Neighborhood, Point};
+use crate::cube::{Floor, Kind, Neighborhood, Point};
 
 #[derive(Debug)]
 pub struct Frozen {
     unchanged: Box<[(Point, Neighborhood)]>,
+    identity: Box<[usize]>, // cubes of units
     collision: BitmapCollision,
+    kinds: Box<[Option<Kind>]>, // kinds of units by position
+    walls: Box<[(Point, Neighborhood)]>,
+    terrain: BitmapCollision,
+    floors: Box<[(Point, Floor)]>,
+    tiles: Box<[Option<Floor>]>,
+    board: Board,
 }
 
 impl Frozen {
-    pub fn new<'a, I>(width: usize, height: usize, it: I) -> Self
+    pub fn new<'a, I, W, F>(board: Board, it: I, walls: W, floors: F) -> Self
     where
-        I: Iterator<Item = &'a [Point]>,
+        I: Iterator<Item = (usize, Kind, &'a [Point])>,
+        W: Iterator<Item = Point>,
+        F: Iterator<Item = (Point, Floor)>,
     {
+        let (width, height) = (board.width(), board.height());
         let mut collision = BitmapCollision::new(width, height);
+        let mut identity = Vec::new();
+        let mut kinds = vec![None; width * height];
         let cubes = {
-            let build = |os: &'a [Point]| {
+            let build = |(id, kind, os): (usize, Kind, &'a [Point])| {
                 let mut c = BitmapCollision::new(width, height);
                 os.iter().for_each(|&o| c.put(o));
                 collision.or(&c);
-                os.iter().map(move |&o| (o, c.neighborhood_or_border(o)))
+                identity.extend(std::iter::repeat_n(id, os.len()));
+                os.iter()
+                    .for_each(|&o| Self::place(&mut kinds, width, height, o, kind));
+                os.iter()
+                    .map(move |&o| (o, board.neighborhood_or_border(&c, o)))
             };
             it.flat_map(build).collect::<Box<_>>()
         };
+        let (walls, terrain) = Self::terrain(board, walls);
+        let floors = floors.collect::<Box<[_]>>();
+        let tiles = Self::tiling(width, height, &floors);
 
         Self {
             unchanged: cubes,
+            identity: identity.into(),
             collision,
+            kinds: kinds.into(),
+            walls,
+            terrain,
+            floors,
+            tiles,
+            board,
+        }
+    }
+
+    pub fn from_units<I, W, F>(board: Board, it: I, walls: W, floors: F) -> Self
+    where
+        I: Iterator<Item = (usize, Kind, Point, Neighborhood)>,
+        W: Iterator<Item = Point>,
+        F: Iterator<Item = (Point, Floor)>,
+    {
+        let (width, height) = (board.width(), board.height());
+        let mut identity = Vec::new();
+        let mut kinds = vec![None; width * height];
+        let cubes = it
+            .map(|(id, kind, o, n)| {
+                identity.push(id);
+                Self::place(&mut kinds, width, height, o, kind);
+                (o, n)
+            })
+            .collect::<Box<[_]>>();
+        let mut collision = BitmapCollision::new(width, height);
+        cubes.iter().for_each(|&(o, _)| collision.put(o));
+        let (walls, terrain) = Self::terrain(board, walls);
+        let floors = floors.collect::<Box<[_]>>();
+        let tiles = Self::tiling(width, height, &floors);
+
+        Self {
+            unchanged: cubes,
+            identity: identity.into(),
+            collision,
+            kinds: kinds.into(),
+            walls,
+            terrain,
+            floors,
+            tiles,
+            board,
         }
     }
 
     pub fn blocked(&self, point: Point) -> bool {
-        !self.collision.available(point)
+        !self.collision.available(point) || self.terrain.existed(point)
+    }
+
+    /// Whether a static cube, rather than a wall or the border, is at the point.
+    pub fn occupied(&self, point: Point) -> bool {
+        self.collision.existed(point)
+    }
+
+    /// The kind of the static cube at the point.
+    pub fn kind(&self, point: Point) -> Option<Kind> {
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        match 0 <= point.x && point.x < width && 0 <= point.y && point.y < height {
+            true => self.kinds[(point.x + point.y * width) as usize],
+            false => None,
+        }
+    }
+
+    pub fn walled(&self, point: Point) -> bool {
+        self.terrain.existed(point)
+    }
+
+    pub fn walls(&self) -> std::slice::Iter<'_, (Point, Neighborhood)> {
+        self.walls.iter()
+    }
+
+    pub fn floor(&self, point: Point) -> Option<Floor> {
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        match 0 <= point.x && point.x < width && 0 <= point.y && point.y < height {
+            true => self.tiles[(point.x + point.y * width) as usize],
+            false => None,
+        }
+    }
+
+    pub fn floors(&self) -> std::slice::Iter<'_, (Point, Floor)> {
+        self.floors.iter()
     }
 
     pub fn iter<'a>(&'a self) -> std::slice::Iter<'a, (Point, Neighborhood)> {
         self.unchanged.iter()
     }
 
+    pub fn identity(&self, index: usize) -> usize {
+        self.identity[index]
+    }
+
     pub fn len(&self) -> usize {
         self.unchanged.len()
     }
@@ -51,4 +150,39 @@ impl Frozen {
     pub fn height(&self) -> usize {
         self.collision.height()
     }
+
+    pub fn board(&self) -> Board {
+        self.board
+    }
+
+    fn terrain<W>(board: Board, it: W) -> (Box<[(Point, Neighborhood)]>, BitmapCollision)
+    where
+        W: Iterator<Item = Point>,
+    {
+        let mut terrain = BitmapCollision::new(board.width(), board.height());
+        let points = it.collect::<Vec<_>>();
+        points.iter().for_each(|&o| terrain.put(o));
+
+        let walls = points
+            .into_iter()
+            .map(|o| (o, board.neighborhood_or_border(&terrain, o)))
+            .collect();
+        (walls, terrain)
+    }
+
+    fn place(kinds: &mut [Option<Kind>], width: usize, height: usize, o: Point, kind: Kind) {
+        if 0 <= o.x && o.x < width as i32 && 0 <= o.y && o.y < height as i32 {
+            kinds[o.x as usize + o.y as usize * width] = Some(kind);
+        }
+    }
+
+    fn tiling(width: usize, height: usize, floors: &[(Point, Floor)]) -> Box<[Option<Floor>]> {
+        let mut tiles = vec![None; width * height];
+        for &(o, floor) in floors.iter() {
+            if 0 <= o.x && o.x < width as i32 && 0 <= o.y && o.y < height as i32 {
+                tiles[o.x as usize + o.y as usize * width] = Some(floor);
+            }
+        }
+        tiles.into()
+    }
 }
@@ -1,8 +1,13 @@
 use crate::cube::{Constraint, Kind, Movement, Neighborhood, Point};
 
+/// Unit is a part of a cube. The `id` of a unit never changes, and `cube` is
+/// the identity of the cube it belongs to, which is taken from the index of
+/// the cube in `Seed::cubes` and kept by the survivor of a merge.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Unit {
     pub id: usize,
+    pub cube: usize,
     pub kind: Kind,
     pub position: Point,
     pub movement: Option<Movement>,
@@ -10,12 +15,96 @@ pub struct Unit {
     pub neighborhood: Neighborhood,
 }
 
+/// Diff contains changed fields only. When serialized, unchanged fields are
+/// omitted, and a `null` movement means the unit stops.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Diff {
     pub id: usize,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub cube: Option<usize>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub kind: Option<Kind>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub position: Option<Point>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "serde_impl::changed"
+        )
+    )]
     pub movement: Option<Option<Movement>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub constraint: Option<Constraint>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub neighborhood: Option<Neighborhood>,
+    /// the position changes by teleporting rather than moving
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub warp: bool,
+}
+
+/// Event tells what happened to cubes in a turn. A cube is referred to by its
+/// identity, i.e. `Unit::cube`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// cubes of the same kind are linked into one
+    Merge { into: usize, from: Vec<usize> },
+    /// cubes of other kinds are taken by a cube
+    Absorb { into: usize, from: Vec<usize> },
+    /// a cube gets `Constraint::Stop`
+    Block { cube: usize, reason: Reason },
+    /// a cube gets `Constraint::Lock`
+    Lock { cube: usize, reason: Reason },
+    /// a cube gets `Constraint::Slap`
+    Slap { cube: usize, reason: Reason },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reason {
+    /// hitting a static obstacle or the border
+    Wall,
+    /// hitting a cube that it cannot link with
+    Cube(usize),
+    /// hitting a cube that it links with
+    Link(usize),
+    /// competing for the same point with a cube
+    Race(usize),
+    /// following a cube that is constrained
+    Follow(usize),
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::{Deserialize, Deserializer};
+
+    /// Tell `null` from a missing field, which is `None` by default.
+    pub fn changed<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
 }
@@ -10,15 +10,16 @@ use crate::cube::{Constraint, Kind, Neighborhood, Point};
 pub struct Snapshot {
     active: Vec<Unit>,
     forzen: Arc<Frozen>,
+    warp: Vec<(usize, Point)>, // units teleported to this snapshot, and their sources
 }
 
 impl Snapshot {
-    pub(crate) fn new(active: Vec<Unit>, forzen: Arc<Frozen>) -> Self {
-        Self { active, forzen }
-    }
-
-    pub fn contains(&self, position: Point) -> bool {
-        self.active.iter().any(|unit| unit.position == position) || self.forzen.blocked(position)
+    pub(crate) fn new(active: Vec<Unit>, forzen: Arc<Frozen>, warp: Vec<(usize, Point)>) -> Self {
+        Self {
+            active,
+            forzen,
+            warp,
+        }
     }
 
     pub fn differ<'a>(&'a self, that: &'a Self) -> impl Iterator<Item = Diff> + 'a {
@@ -31,14 +32,18 @@ impl Snapshot {
         std::iter::zip(self.active.iter(), that.active.iter())
             .take(maximum)
             .filter(|(l, r)| {
-                l.kind != r.kind
+                l.cube != r.cube
+                    || l.kind != r.kind
                     || l.position != r.position
                     || l.movement != r.movement
                     || l.constraint != r.constraint
                     || l.neighborhood != r.neighborhood
             })
             .map(|(l, r)| Diff {
+                warp: l.position != r.position
+                    && (that.warped(r.id, l.position) || self.warped(l.id, r.position)),
                 id: r.id,
+                cube: (l.cube != r.cube).then(|| r.cube),
                 kind: (l.kind != r.kind).then(|| r.kind),
                 position: (l.position != r.position).then(|| r.position),
                 movement: (l.movement != r.movement).then(|| r.movement),
@@ -47,6 +52,10 @@ impl Snapshot {
             })
     }
 
+    fn warped(&self, id: usize, source: Point) -> bool {
+        self.warp.contains(&(id, source))
+    }
+
     pub fn iter(&self) -> SnapshotIter<'_> {
         SnapshotIter {
             source: self,
@@ -82,7 +91,8 @@ impl<'a> Iterator for SnapshotIter<'a> {
             if let Some((index, (point, neighborhood))) = iter.next() {
                 return Some(Unit {
                     id: index + self.source.active.len(),
-                    kind: Kind::White,
+                    cube: self.source.forzen.identity(index),
+                    kind: self.source.forzen.kind(*point).unwrap_or(Kind::White),
                     position: point.clone(),
                     movement: None,
                     constraint: Constraint::Free,
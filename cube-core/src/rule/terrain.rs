@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use super::lookup::{BitmapCollision, Collision};
+use crate::cube::Point;
+
+type Wire = (Box<[Point]>, Box<[Point]>); // plates and doors
+
+/// Terrain is the mutable part of the background. Doors are opened while
+/// any plate wired to them is pressed, or while something stands in them.
+#[derive(Clone, Debug)]
+pub struct Terrain {
+    wires: Arc<[Wire]>,
+    closed: BitmapCollision,
+}
+
+impl Terrain {
+    pub fn new<'a, I>(width: usize, height: usize, it: I) -> Self
+    where
+        I: Iterator<Item = (&'a [Point], &'a [Point])>,
+    {
+        let wires = it
+            .map(|(plates, doors)| (plates.into(), doors.into()))
+            .collect::<Arc<[Wire]>>();
+        let mut closed = BitmapCollision::new(width, height);
+        for (_, doors) in wires.iter() {
+            doors.iter().for_each(|&o| closed.put(o));
+        }
+        Self { wires, closed }
+    }
+
+    pub fn blocked(&self, point: Point) -> bool {
+        self.closed.existed(point)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wires.is_empty()
+    }
+
+    pub fn update(&mut self, occupied: impl Fn(Point) -> bool) {
+        let mut closed = BitmapCollision::new(self.closed.width(), self.closed.height());
+        for (plates, doors) in self.wires.iter() {
+            if !plates.iter().any(|&o| occupied(o)) {
+                for &o in doors.iter().filter(|&&o| !occupied(o)) {
+                    closed.put(o);
+                }
+            }
+        }
+        self.closed = closed;
+    }
+
+    pub fn wires(&self) -> impl Iterator<Item = (&[Point], &[Point])> + '_ {
+        self.wires
+            .iter()
+            .map(|(plates, doors)| (plates.as_ref(), doors.as_ref()))
+    }
+
+    pub fn plates(&self) -> impl Iterator<Item = Point> + '_ {
+        self.wires
+            .iter()
+            .flat_map(|(plates, _)| plates.iter().copied())
+    }
+
+    /// Doors with whether they are open.
+    pub fn doors(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
+        (self.wires.iter())
+            .flat_map(|(_, doors)| doors.iter())
+            .map(|&o| (o, !self.blocked(o)))
+    }
+}
@@ -1,34 +1,143 @@
-use super::cube::{Kind, Movement, Point};
+use super::{
+    cube::{Floor, Kind, Movement, Point},
+    objective::Objective,
+};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Seed {
     pub info: Info,
     pub size: Size,
     pub cubes: Vec<Cube>,
+    #[cfg_attr(feature = "serde", serde(rename = "destinations"))]
     pub destnations: Vec<Point>,
+    /// destinations that only accept a cube of the given kind
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub demands: Vec<(Point, Kind)>,
+    /// immovable terrain, which is not a cube and never covers destinations
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub walls: Vec<Point>,
+    /// tiles under cubes, such as ice
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub floors: Vec<(Point, Floor)>,
+    /// plates that open doors while pressed
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub wires: Vec<Wire>,
+    /// whether cubes leaving an edge come back from the opposite one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub wrap: bool,
+    /// direction that cubes fall to while nothing holds them
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub gravity: Option<Movement>,
+    /// kinds steered by separate controllers, which are always controlled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub players: Vec<Kind>,
+    /// conditions to complete or fail, or just cover all destinations if empty
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub objectives: Vec<Objective>,
 }
 
-#[derive(Clone)]
+impl Seed {
+    /// All destinations, with the kind each of them demands.
+    pub fn goals(&self) -> impl Iterator<Item = (Point, Option<Kind>)> + '_ {
+        let plain = self.destnations.iter().map(|&o| (o, None));
+        let colored = self.demands.iter().map(|&(o, kind)| (o, Some(kind)));
+        plain.chain(colored)
+    }
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
     pub title: String,
     pub author: String,
+    /// the number of moves expected to solve the level
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub par: Option<usize>,
+    /// the level fails after more moves than this
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub max_moves: Option<usize>,
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Cube {
     pub kind: Kind,
     pub body: Vec<Point>,
     pub command: Option<Command>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Size {
     pub width: i32,
     pub height: i32,
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wire {
+    pub plates: Vec<Point>,
+    pub doors: Vec<Point>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Command {
     pub is_loop: bool,
+    /// movements and their times, where zero times waits until the player moves
     pub movements: Vec<(Option<Movement>, usize)>,
 }
+
+#[cfg(test)]
+impl Seed {
+    /// An empty level of the size, which tests fill in.
+    pub(crate) fn sized(width: i32, height: i32) -> Self {
+        Self {
+            size: Size { width, height },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+impl Cube {
+    /// A cube of a single unit without commands.
+    pub(crate) fn unit(kind: Kind, o: Point) -> Self {
+        Self {
+            kind,
+            body: vec![o],
+            command: None,
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::{
+    cube::{Input, RuleSet},
+    objective::{Judge, Outcome},
+    rule::Collection,
+    seed::Seed,
+};
+
+/////////////////////////////////////////////////////////////////////////////
+// export
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Solution {
+    /// the shortest inputs that complete the level
+    Found(Vec<Input>),
+    /// no reachable state completes the level
+    Unsolvable,
+    /// gave up after visiting too many states
+    Exhausted,
+}
+
+/// Search for the shortest inputs to solve a level with at most `limit`
+/// distinct states visited.
+pub fn solve(seed: &Seed, rules: &RuleSet, limit: usize) -> Solution {
+    search(&Collection::from((seed, rules)), &Judge::from(seed), limit)
+}
+
+pub(crate) fn search(start: &Collection, judge: &Judge, limit: usize) -> Solution {
+    match judge.outcome(start) {
+        Outcome::Complete => return Solution::Found(Vec::new()),
+        Outcome::Failed => return Solution::Unsolvable,
+        Outcome::Playing => {}
+    }
+
+    // every visited state except the start one is traced by its parent and
+    // the input leading to it.
+    let mut trace: Vec<(Option<usize>, Input)> = Vec::new();
+    let mut visit = HashSet::from([(start.image(), judge.stage(start))]);
+    let mut queue = VecDeque::from([(None, start.clone())]);
+    let inputs = judge.inputs();
+
+    while let Some((parent, collection)) = queue.pop_front() {
+        for &input in inputs.iter() {
+            let mut next = collection.clone();
+            next.commit(input);
+            if !visit.insert((next.image(), judge.stage(&next))) {
+                continue;
+            }
+
+            trace.push((parent, input));
+            match judge.outcome(&next) {
+                Outcome::Complete => return Solution::Found(backtrack(&trace, trace.len() - 1)),
+                Outcome::Failed => continue,
+                Outcome::Playing => {}
+            }
+            if visit.len() > limit {
+                return Solution::Exhausted;
+            }
+
+            queue.push_back((Some(trace.len() - 1), next));
+        }
+    }
+
+    Solution::Unsolvable
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// internal
+
+fn backtrack(trace: &[(Option<usize>, Input)], last: usize) -> Vec<Input> {
+    let mut output = Vec::new();
+    let mut cursor = Some(last);
+    while let Some(index) = cursor {
+        let (parent, input) = trace[index];
+        output.push(input);
+        cursor = parent;
+    }
+    output.reverse();
+    output
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cube::{Kind, Movement, Point},
+        objective::Objective,
+        seed::{Command, Cube},
+    };
+
+    fn make(cubes: Vec<Cube>, destnations: Vec<Point>) -> Seed {
+        Seed {
+            cubes,
+            destnations,
+            ..Seed::sized(3, 3)
+        }
+    }
+
+    #[test]
+    fn shortest() {
+        /*****
+         *G  *
+         *   *
+         *  x*
+         *****/
+        let seed = make(
+            vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            vec![Point::new(2, 2)],
+        );
+        match solve(&seed, &RuleSet::default(), 1000) {
+            Solution::Found(inputs) => {
+                assert_eq!(inputs.len(), 4);
+                assert_eq!(inputs.iter().filter(|m| m.is_empty()).count(), 0);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let seed = make(Vec::new(), Vec::new());
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Found(Vec::new())
+        );
+    }
+
+    #[test]
+    fn time_dependent() {
+        /*****
+         *R x*
+         *   *
+         *   *
+         *****/
+        let red = |movements| Cube {
+            kind: Kind::Red,
+            body: vec![Point::new(0, 0)],
+            command: Some(Command {
+                is_loop: false,
+                movements,
+            }),
+        };
+
+        let seed = make(
+            vec![red(vec![(None, 2), (Some(Movement::Right), 2)])],
+            vec![Point::new(2, 0)],
+        );
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Found(vec![Input::new(); 4])
+        );
+
+        let seed = make(
+            vec![red(vec![(Some(Movement::Right), 1)])],
+            vec![Point::new(2, 0)],
+        );
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Unsolvable
+        );
+
+        let seed = make(
+            vec![
+                red(vec![(Some(Movement::Right), 1)]),
+                Cube::unit(Kind::Green, Point::new(0, 2)),
+            ],
+            vec![Point::new(2, 0)],
+        );
+        assert_eq!(solve(&seed, &RuleSet::default(), 1), Solution::Exhausted);
+    }
+
+    #[test]
+    fn objectives() {
+        /*****
+         *G R*
+         *   *
+         *  x*
+         *****/
+        let mut seed = make(
+            vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Red, Point::new(2, 0)),
+            ],
+            vec![Point::new(2, 2)],
+        );
+
+        seed.objectives = vec![Objective::Survive(3)];
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Found(vec![Input::new(); 3])
+        );
+
+        seed.objectives = vec![Objective::Eliminate(Kind::Green)];
+        match solve(&seed, &RuleSet::default(), 1000) {
+            Solution::Found(inputs) => assert_eq!(inputs.len(), 2),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        seed.objectives = vec![Objective::Eliminate(Kind::Green), Objective::Protect(0)];
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Unsolvable
+        );
+
+        seed.objectives = vec![Objective::Within(3)];
+        assert_eq!(
+            solve(&seed, &RuleSet::default(), 1000),
+            Solution::Unsolvable
+        );
+    }
+}
@@ -1,42 +1,113 @@
+use std::collections::VecDeque;
+
 use super::{
-    cube::{Kind, Motion, Movement, Point},
-    rule::{Collection, Diff, Snapshot, Unit},
+    cube::{Floor, Input, Kind, Motion, Neighborhood, Point, RuleSet},
+    objective::{Judge, Objective, Outcome},
+    rule::{Archive, Board, Collection, Diff, Event, Snapshot, Unit},
     seed::{Cube, Seed},
+    solver::{search, Solution},
 };
 
+#[derive(Clone)]
 pub struct CubeCore {
-    dest: Vec<Point>,
-    last: Option<(Collection, Snapshot)>,
+    judge: Judge,
+    back: VecDeque<(Collection, Snapshot)>, // undo stack
+    next: Vec<(Collection, Snapshot)>,      // redo stack
     base: (Collection, Snapshot),
+    keep: Option<usize>, // the most turns to undo, or unbounded
 }
 
-impl CubeCore {
-    pub fn new(seed: &Seed) -> Self {
-        fn convert(cube: &Cube) -> (Kind, &[Point], Motion) {
-            (
-                cube.kind,
-                cube.body.as_slice(),
-                match &cube.command {
-                    None => Motion::new(),
-                    Some(command) => {
-                        Motion::from_sequence(command.is_loop, command.movements.iter().cloned())
-                    }
-                },
-            )
+/// Save is a self-contained copy of a game in progress, which doesn't include
+/// the history of undo and redo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Save {
+    version: u32,
+    dest: Vec<(Point, Option<Kind>)>,
+    objectives: Vec<Objective>,
+    players: Vec<Kind>,
+    base: Archive,
+}
+
+impl Save {
+    /// The format of saves. It is bumped whenever the format changes, and
+    /// saves of other versions are rejected on restore.
+    ///
+    /// 1. The first format.
+    /// 2. Cubes keep identities and fates, destinations demand kinds, static
+    ///    cubes keep kinds, and rule sets, objectives and counters are saved.
+    /// 3. Walls, floors, wires, wrapping, gravity, slides of cubes and players
+    ///    are saved.
+    pub const VERSION: u32 = 3;
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RestoreError {
+    UnsupportedVersion(u32),
+    Corrupted,
+}
+
+impl std::fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "unsupported save version {}", v),
+            Self::Corrupted => write!(f, "corrupted save"),
         }
+    }
+}
 
-        let dest = seed.destnations.clone();
-        let collection = Collection::new(
-            seed.size.width.max(1) as usize,
-            seed.size.height.max(1) as usize,
-            seed.cubes.iter().map(convert),
-        );
+impl std::error::Error for RestoreError {}
+
+impl CubeCore {
+    // states to search for a hint, about a second or so in release builds.
+    const HINT_LIMIT: usize = 20_000;
+
+    pub fn new(seed: &Seed, rules: &RuleSet) -> Self {
+        let judge = Judge::from(seed);
+        let collection = Collection::from((seed, rules));
         let snapshot = collection.snapshot();
 
         Self {
-            dest,
-            last: None,
+            judge,
+            back: VecDeque::new(),
+            next: Vec::new(),
+            base: (collection, snapshot),
+            keep: None,
+        }
+    }
+
+    /// Rebuild a game from a save. The history starts empty.
+    pub fn restore(save: &Save) -> Result<Self, RestoreError> {
+        if save.version != Save::VERSION {
+            return Err(RestoreError::UnsupportedVersion(save.version));
+        }
+
+        let collection = Collection::from_archive(&save.base).ok_or(RestoreError::Corrupted)?;
+        let snapshot = collection.snapshot();
+        Ok(Self {
+            judge: Judge {
+                dest: save.dest.clone(),
+                objectives: save.objectives.clone(),
+                players: save.players.clone(),
+            },
+            back: VecDeque::new(),
+            next: Vec::new(),
             base: (collection, snapshot),
+            keep: None,
+        })
+    }
+
+    pub fn save(&self) -> Save {
+        Save {
+            version: Save::VERSION,
+            dest: self.judge.dest.clone(),
+            objectives: self.judge.objectives.clone(),
+            players: self.judge.players.clone(),
+            base: self.base.0.archive(),
         }
     }
 
@@ -44,37 +115,184 @@ impl CubeCore {
         self.base.1.iter()
     }
 
-    pub fn goals(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
-        self.dest.iter().map(|&o| (o, self.base.1.contains(o)))
+    /// Walls with their neighborhoods among walls, where the border counts.
+    pub fn walls(&self) -> impl Iterator<Item = (Point, Neighborhood)> + '_ {
+        self.base.0.walls()
+    }
+
+    pub fn floors(&self) -> impl Iterator<Item = (Point, Floor)> + '_ {
+        self.base.0.floors()
+    }
+
+    pub fn plates(&self) -> impl Iterator<Item = Point> + '_ {
+        self.base.0.plates()
+    }
+
+    /// Doors with whether they are open.
+    pub fn doors(&self) -> impl Iterator<Item = (Point, bool)> + '_ {
+        self.base.0.doors()
+    }
+
+    /// Destinations with the kind they demand, and whether they are covered.
+    pub fn goals(&self) -> impl Iterator<Item = (Point, Option<Kind>, bool)> + '_ {
+        self.judge
+            .dest
+            .iter()
+            .map(|&(o, kind)| (o, kind, self.base.0.covers(o, kind)))
+    }
+
+    /// Whether the level is complete or failed by its objectives.
+    pub fn outcome(&self) -> Outcome {
+        self.judge.outcome(&self.base.0)
+    }
+
+    /// Whether the level can never be complete from the current state, by a
+    /// cheap check that misses many dead ends.
+    pub fn hopeless(&self) -> bool {
+        self.judge.hopeless(&self.base.0)
+    }
+
+    /// Whether the level can never be complete from the current state. It
+    /// searches at most `limit` states, and says `false` if unsure.
+    pub fn dead_end(&self, limit: usize) -> bool {
+        self.hopeless() || search(&self.base.0, &self.judge, limit) == Solution::Unsolvable
+    }
+
+    /// Suggest the next input on the shortest way to complete the level from
+    /// the current state. It gives `None` if waiting is the best, or if no way
+    /// is found within a search of limited states.
+    pub fn hint(&self) -> Option<Input> {
+        match search(&self.base.0, &self.judge, Self::HINT_LIMIT) {
+            Solution::Found(inputs) => inputs.first().copied().filter(|o| !o.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// The number of turns committed, including those without inputs.
+    pub fn turn(&self) -> usize {
+        self.base.0.turn()
+    }
+
+    /// The number of turns committed with inputs.
+    pub fn moves(&self) -> usize {
+        self.base.0.moves()
     }
 
-    pub fn commit(&mut self, movement: Option<Movement>) -> impl Iterator<Item = Diff> + '_ {
+    /// Events of the turn that leads to the current state.
+    pub fn events(&self) -> &[Event] {
+        self.base.0.events()
+    }
+
+    /// The identity of the cube that absorbed or merged a cube, if any.
+    pub fn absorbed_into(&self, cube: usize) -> Option<usize> {
+        self.base.0.absorbed_into(cube)
+    }
+
+    /// Play one turn. The input is either a single movement for all controlled
+    /// kinds, or an `Input` that steers each kind on its own.
+    pub fn commit(&mut self, input: impl Into<Input>) -> impl Iterator<Item = Diff> + '_ {
         let mut base = self.base.0.clone();
-        base.commit(movement);
+        base.commit(input.into());
         let snapshot = base.snapshot();
-        let mut base = (base, snapshot);
+        let prev = std::mem::replace(&mut self.base, (base, snapshot));
+        let diff = prev.1.differ(&self.base.1).collect::<Vec<_>>();
 
-        std::mem::swap(&mut self.base, &mut base);
-        let last = self.last.insert(base);
-        last.1.differ(&self.base.1)
+        self.next.clear();
+        self.record(prev);
+        diff.into_iter()
     }
 
-    pub fn remake(&mut self, movement: Option<Movement>) -> impl Iterator<Item = Diff> + '_ {
-        let pair = match &mut self.last {
-            None => (&self.base.1, &self.base.1),
+    pub fn remake(&mut self, input: impl Into<Input>) -> impl Iterator<Item = Diff> + '_ {
+        let diff = match self.back.back() {
+            None => Vec::new(),
             Some(last) => {
                 let mut base = last.0.clone();
-                base.commit(movement);
+                base.commit(input.into());
+                let snapshot = base.snapshot();
+
+                self.next.clear();
+                let prev = std::mem::replace(&mut self.base, (base, snapshot));
+                prev.1.differ(&self.base.1).collect()
+            }
+        };
+
+        diff.into_iter()
+    }
 
-                last.1 = base.snapshot();
-                std::mem::swap(&mut self.base.1, &mut last.1);
-                self.base.0 = base;
+    /// Step back one turn. The returned diffs lead from the current state to
+    /// the previous one, and nothing happens if there is no history left.
+    pub fn undo(&mut self) -> impl Iterator<Item = Diff> + '_ {
+        match self.back.pop_back() {
+            None => self.base.1.differ(&self.base.1),
+            Some(last) => {
+                self.next.push(std::mem::replace(&mut self.base, last));
+                let prev = &self.next[self.next.len() - 1];
+                prev.1.differ(&self.base.1)
+            }
+        }
+    }
 
-                (&last.1, &self.base.1)
+    /// Replay one turn that has been undone. Any `commit` or `remake` after an
+    /// `undo` discards the turns that could be redone.
+    pub fn redo(&mut self) -> impl Iterator<Item = Diff> + '_ {
+        let diff = match self.next.pop() {
+            None => Vec::new(),
+            Some(next) => {
+                let prev = std::mem::replace(&mut self.base, next);
+                let diff = prev.1.differ(&self.base.1).collect();
+                self.record(prev);
+                diff
             }
         };
 
-        pair.0.differ(&pair.1)
+        diff.into_iter()
+    }
+
+    /// A copy of the game at the current state, without the history of undo
+    /// and redo, which is cheap to send away for a search.
+    pub fn present(&self) -> Self {
+        Self {
+            judge: self.judge.clone(),
+            back: VecDeque::new(),
+            next: Vec::new(),
+            base: self.base.clone(),
+            keep: self.keep,
+        }
+    }
+
+    /// Whether cubes of the kind follow inputs.
+    pub fn controlled(&self, kind: Kind) -> bool {
+        self.base.0.rule().controlled(kind)
+    }
+
+    /// The number of turns that can be undone.
+    pub fn history_len(&self) -> usize {
+        self.back.len()
+    }
+
+    /// Keep at most `keep` turns to undo, dropping the oldest ones. The
+    /// history is unbounded by default.
+    pub fn set_history_limit(&mut self, keep: Option<usize>) {
+        self.keep = keep;
+        self.trim();
+    }
+
+    fn record(&mut self, prev: (Collection, Snapshot)) {
+        self.back.push_back(prev);
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        if let Some(keep) = self.keep {
+            let drop = self.back.len().saturating_sub(keep);
+            self.back.drain(..drop);
+        }
+    }
+
+    /// A stable hash of the current position, including the progress of
+    /// commands. It ignores the order of cubes and the history.
+    pub fn fingerprint(&self) -> u64 {
+        self.base.0.fingerprint()
     }
 
     pub fn width(&self) -> usize {
@@ -85,3 +303,395 @@ impl CubeCore {
         self.base.0.height()
     }
 }
+
+impl From<(&Seed, &RuleSet)> for Collection {
+    fn from((seed, rules): (&Seed, &RuleSet)) -> Self {
+        fn convert(cube: &Cube) -> (Kind, &[Point], Motion) {
+            (
+                cube.kind,
+                cube.body.as_slice(),
+                match &cube.command {
+                    None => Motion::new(),
+                    Some(command) => {
+                        Motion::from_sequence(command.is_loop, command.movements.iter().cloned())
+                    }
+                },
+            )
+        }
+
+        let (width, height) = (seed.size.width.max(1), seed.size.height.max(1));
+        let rules = (seed.players.iter()).fold(rules.clone(), |rules, &kind| rules.control(kind));
+        Collection::new(
+            Board::new(width as usize, height as usize, seed.wrap),
+            &rules,
+            seed.cubes.iter().map(convert),
+            seed.walls.iter().cloned(),
+            seed.floors.iter().cloned(),
+            (seed.wires.iter()).map(|wire| (wire.plates.as_slice(), wire.doors.as_slice())),
+            seed.gravity,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cube::Movement, seed::Command};
+
+    #[test]
+    fn undo_and_redo() {
+        /*****
+         *G  *
+         *   *
+         * Rx*
+         *****/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube {
+                    kind: Kind::Red,
+                    body: vec![Point::new(1, 2)],
+                    command: Some(Command {
+                        is_loop: true,
+                        movements: vec![(Some(Movement::Up), 1), (Some(Movement::Down), 1)],
+                    }),
+                },
+            ],
+            destnations: vec![Point::new(2, 2)],
+            ..Seed::sized(3, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let start = game.iter().collect::<Vec<_>>();
+        assert_eq!(game.history_len(), 0);
+        assert_eq!(game.undo().count(), 0);
+        assert_eq!(game.redo().count(), 0);
+
+        let moves = [Movement::Right, Movement::Down, Movement::Right];
+        let mut states = vec![start.clone()];
+        for movement in moves {
+            game.commit(Some(movement)).for_each(drop);
+            states.push(game.iter().collect());
+        }
+        assert_eq!(game.history_len(), moves.len());
+
+        // step back to the beginning, and diffs should lead to old states.
+        for expected in states.iter().rev().skip(1) {
+            let mut current = game.iter().collect::<Vec<_>>();
+            for diff in game.undo().collect::<Vec<_>>() {
+                let unit = &mut current[diff.id];
+                unit.cube = diff.cube.unwrap_or(unit.cube);
+                unit.kind = diff.kind.unwrap_or(unit.kind);
+                unit.position = diff.position.unwrap_or(unit.position);
+                unit.movement = diff.movement.unwrap_or(unit.movement);
+                unit.constraint = diff.constraint.unwrap_or(unit.constraint);
+                unit.neighborhood = diff.neighborhood.unwrap_or(unit.neighborhood);
+            }
+            assert_eq!(&current, expected);
+            assert_eq!(&game.iter().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(game.history_len(), 0);
+
+        // replay all of them.
+        for expected in states.iter().skip(1) {
+            assert!(game.redo().count() > 0);
+            assert_eq!(&game.iter().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(game.redo().count(), 0);
+        assert_eq!(game.history_len(), moves.len());
+
+        // a new commit drops the redo stack.
+        game.undo().for_each(drop);
+        game.commit(None).for_each(drop);
+        assert_eq!(game.redo().count(), 0);
+        assert_eq!(game.history_len(), moves.len());
+
+        // remake rewrites exactly the last turn, even one without inputs.
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        game.commit(None).for_each(drop);
+        assert_ne!(game.remake(Some(Movement::Right)).count(), 0);
+        assert_eq!(game.history_len(), 1);
+        assert_eq!(game.iter().next().unwrap().position, Point::new(1, 0));
+        game.undo().for_each(drop);
+        assert_eq!(game.iter().collect::<Vec<_>>(), start);
+
+        // the history is unbounded unless it is limited.
+        for _ in 0..1_100 {
+            game.commit(None).for_each(drop);
+        }
+        assert_eq!(game.history_len(), 1_100);
+        game.set_history_limit(Some(1_000));
+        assert_eq!(game.history_len(), 1_000);
+        game.commit(None).for_each(drop);
+        assert_eq!(game.history_len(), 1_000);
+    }
+
+    #[test]
+    fn fingerprint() {
+        /*****
+         *G W*
+         *   *
+         *R  *
+         *****/
+
+        let cubes = vec![
+            Cube::unit(Kind::Green, Point::new(0, 0)),
+            Cube {
+                kind: Kind::Red,
+                body: vec![Point::new(0, 2)],
+                command: Some(Command {
+                    is_loop: true,
+                    movements: vec![(Some(Movement::Right), 1), (Some(Movement::Left), 1)],
+                }),
+            },
+            Cube::unit(Kind::White, Point::new(2, 0)),
+        ];
+        let make = |cubes| Seed {
+            cubes,
+            ..Seed::sized(3, 3)
+        };
+
+        let mut game = CubeCore::new(&make(cubes.clone()), &RuleSet::default());
+        let start = game.fingerprint();
+        assert_eq!(start, game.fingerprint());
+
+        // the order of cubes does not matter.
+        let other = CubeCore::new(
+            &make(cubes.iter().rev().cloned().collect()),
+            &RuleSet::default(),
+        );
+        assert_eq!(start, other.fingerprint());
+
+        // but obstacles do.
+        let other = CubeCore::new(&make(cubes[..2].to_vec()), &RuleSet::default());
+        assert_ne!(start, other.fingerprint());
+
+        // positions and motions are both considered.
+        game.commit(Some(Movement::Down)).for_each(drop);
+        let step = game.fingerprint();
+        assert_ne!(start, step);
+        game.commit(Some(Movement::Up)).for_each(drop);
+        assert_eq!(start, game.fingerprint());
+        game.commit(None).for_each(drop);
+        assert_ne!(start, game.fingerprint());
+        assert_ne!(step, game.fingerprint());
+    }
+
+    #[test]
+    fn dead_end() {
+        /*****
+         *G  *
+         *****/
+
+        let seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            destnations: vec![Point::new(2, 0)],
+            ..Seed::sized(3, 1)
+        };
+
+        // still solvable.
+        let game = CubeCore::new(&seed, &RuleSet::default());
+        assert!(!game.dead_end(0));
+        assert!(!game.dead_end(1000));
+
+        // too few units are left to cover all destinations, which needs no
+        // search.
+        let less = Seed {
+            destnations: vec![Point::new(1, 0), Point::new(2, 0)],
+            ..seed.clone()
+        };
+        let game = CubeCore::new(&less, &RuleSet::default());
+        assert!(game.hopeless());
+        assert!(game.dead_end(0));
+
+        // or too few units that may become the demanded kind, where green
+        // never turns into red without absorption.
+        let less = Seed {
+            destnations: vec![],
+            demands: vec![(Point::new(2, 0), Kind::Red)],
+            ..seed.clone()
+        };
+        let game = CubeCore::new(&less, &RuleSet::new().control(Kind::Green));
+        assert!(game.hopeless());
+        assert!(game.dead_end(0));
+
+        // the cube is stuck behind a wall, which is found by a search.
+        let stuck = Seed {
+            walls: vec![Point::new(1, 0)],
+            ..seed.clone()
+        };
+        let game = CubeCore::new(&stuck, &RuleSet::default());
+        assert!(!game.hopeless());
+        assert!(game.dead_end(1000));
+    }
+
+    #[test]
+    fn hint() {
+        /*****
+         *G  *
+         *   *
+         *  x*
+         *****/
+
+        let seed = Seed {
+            cubes: vec![Cube::unit(Kind::Green, Point::new(0, 0))],
+            destnations: vec![Point::new(2, 2)],
+            ..Seed::sized(3, 3)
+        };
+
+        // follow hints to the end, and nothing is left to suggest.
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        while game.outcome() == Outcome::Playing && game.turn() < 10 {
+            let hint = game.hint().unwrap();
+            game.commit(hint).for_each(drop);
+
+            // a copy without history gives the same hint.
+            let present = game.present();
+            assert_eq!(present.history_len(), 0);
+            assert_eq!(present.fingerprint(), game.fingerprint());
+            assert_eq!(present.hint(), game.hint());
+        }
+        assert!(game.controlled(Kind::Green));
+        assert!(!game.controlled(Kind::Red));
+        assert_eq!(game.turn(), 4);
+        assert_eq!(game.hint(), None);
+    }
+
+    #[test]
+    fn save_and_restore() {
+        /******
+         *G B *
+         *    *
+         *R  W*
+         ******/
+
+        let seed = Seed {
+            cubes: vec![
+                Cube::unit(Kind::Green, Point::new(0, 0)),
+                Cube::unit(Kind::Blue, Point::new(2, 0)),
+                Cube {
+                    kind: Kind::Red,
+                    body: vec![Point::new(0, 2)],
+                    command: Some(Command {
+                        is_loop: true,
+                        movements: vec![(Some(Movement::Right), 2), (Some(Movement::Left), 2)],
+                    }),
+                },
+                Cube::unit(Kind::White, Point::new(3, 2)),
+            ],
+            destnations: vec![Point::new(3, 0)],
+            ..Seed::sized(4, 3)
+        };
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        game.commit(Some(Movement::Right)).for_each(drop);
+        game.commit(None).for_each(drop);
+        game.commit(Some(Movement::Down)).for_each(drop);
+
+        // the blue cube has been absorbed.
+        let greens = game
+            .iter()
+            .filter(|unit| unit.kind == Kind::Green)
+            .collect::<Vec<_>>();
+        assert_eq!(greens.len(), 2);
+        assert!(greens
+            .iter()
+            .all(|unit| unit.neighborhood != Neighborhood::new()));
+
+        let save = game.save();
+        assert_eq!(save.version(), Save::VERSION);
+        let mut other = CubeCore::restore(&save).unwrap();
+        assert_eq!(other.history_len(), 0);
+        assert_eq!(other.save(), save);
+
+        let same = |l: &CubeCore, r: &CubeCore| {
+            assert_eq!(l.fingerprint(), r.fingerprint());
+            assert_eq!(l.iter().collect::<Vec<_>>(), r.iter().collect::<Vec<_>>());
+            assert_eq!(l.goals().collect::<Vec<_>>(), r.goals().collect::<Vec<_>>());
+        };
+        same(&game, &other);
+        for input in [None, Some(Movement::Up), None, Some(Movement::Left), None] {
+            let l = game.commit(input).collect::<Vec<_>>();
+            let r = other.commit(input).collect::<Vec<_>>();
+            assert_eq!(l, r);
+            same(&game, &other);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde() {
+        use crate::seed::Info;
+
+        let seed = Seed {
+            info: Info {
+                title: "test".into(),
+                author: "test".into(),
+                par: None,
+                max_moves: None,
+            },
+            cubes: vec![Cube {
+                kind: Kind::Red,
+                body: vec![Point::new(0, 0)],
+                command: Some(Command {
+                    is_loop: false,
+                    movements: vec![(None, 1), (Some(Movement::Right), 1)],
+                }),
+            }],
+            destnations: vec![Point::new(1, 0)],
+            ..Seed::sized(2, 1)
+        };
+        let text = serde_json::to_string(&seed).unwrap();
+        assert_eq!(
+            text,
+            concat!(
+                r#"{"info":{"title":"test","author":"test"},"size":{"width":2,"height":1},"#,
+                r#""cubes":[{"kind":"Red","body":[{"x":0,"y":0}],"#,
+                r#""command":{"is_loop":false,"movements":[[null,1],["Right",1]]}}],"#,
+                r#""destinations":[{"x":1,"y":0}]}"#,
+            )
+        );
+        let seed = serde_json::from_str::<Seed>(&text).unwrap();
+        assert_eq!(serde_json::to_string(&seed).unwrap(), text);
+
+        let mut game = CubeCore::new(&seed, &RuleSet::default());
+        let units = game.iter().collect::<Vec<_>>();
+        let text = serde_json::to_string(&units).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<Unit>>(&text).unwrap(), units);
+
+        let diffs = [
+            game.commit(None).collect::<Vec<_>>(),
+            game.commit(None).collect::<Vec<_>>(),
+            game.commit(None).collect::<Vec<_>>(),
+        ]
+        .concat();
+        let text = serde_json::to_string(&diffs).unwrap();
+        assert_eq!(
+            text,
+            concat!(
+                r#"[{"id":0,"position":{"x":1,"y":0},"movement":"Right"},"#,
+                r#"{"id":0,"movement":null}]"#,
+            )
+        );
+        assert_eq!(serde_json::from_str::<Vec<Diff>>(&text).unwrap(), diffs);
+
+        let save = game.save();
+        let text = serde_json::to_string(&save).unwrap();
+        assert_eq!(serde_json::from_str::<Save>(&text).unwrap(), save);
+
+        let version = format!(r#""version":{}"#, Save::VERSION);
+        for old in [0, 1, 2] {
+            let text = text.replacen(&version, &format!(r#""version":{}"#, old), 1);
+            let save = serde_json::from_str::<Save>(&text).unwrap();
+            assert_eq!(
+                CubeCore::restore(&save).err(),
+                Some(RestoreError::UnsupportedVersion(old))
+            );
+        }
+
+        // fields are never filled in by defaults.
+        let text = text.replacen(r#""walls":[],"#, "", 1);
+        assert!(serde_json::from_str::<Save>(&text).is_err());
+    }
+}